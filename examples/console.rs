@@ -0,0 +1,140 @@
+use imgui::{
+  Condition, FocusedWidget, HistoryDirection, InputTextCallback, InputTextCallbackHandler,
+  TextCallbackData,
+};
+use macroquad::prelude::*;
+
+use imgui_macroquad::ImGuiContext;
+
+const COMMANDS: &[&str] = &["clear", "help", "history", "quit"];
+
+#[macroquad::main("Console")]
+async fn main() {
+  let mut ctx = ImGuiContext::default();
+  let mut console = Console::default();
+
+  loop {
+    clear_background(Color::new(0.16, 0.16, 0.16, 1.));
+
+    ctx.ui(|ctx, ui| {
+      ui.window("Console")
+        .size([520., 600.], Condition::FirstUseEver)
+        .build(|| console.draw(ctx, ui));
+    });
+
+    ctx.draw();
+
+    if console.quit {
+      break;
+    }
+
+    next_frame().await;
+  }
+}
+
+#[derive(Default)]
+struct Console {
+  input: String,
+  log: Vec<String>,
+  history: Vec<String>,
+  history_position: Option<usize>,
+  quit: bool,
+}
+
+impl Console {
+  fn draw(&mut self, ctx: &ImGuiContext, ui: &imgui::Ui) {
+    let footer = ui.frame_height_with_spacing();
+
+    ctx.log_view(ui, "log", &self.log, [0., -footer]);
+
+    ui.separator();
+
+    let submitted = ui
+      .input_text("##input", &mut self.input)
+      .enter_returns_true(true)
+      .callback(
+        InputTextCallback::COMPLETION | InputTextCallback::HISTORY,
+        Callbacks {
+          history: &self.history,
+          position: &mut self.history_position,
+          log: &mut self.log,
+        },
+      )
+      .build();
+
+    if submitted {
+      let command = std::mem::take(&mut self.input);
+      self.history_position = None;
+      self.run(command.trim());
+      // keep focus on the input after submitting, like a terminal
+      ui.set_keyboard_focus_here_with_offset(FocusedWidget::Previous);
+    }
+  }
+
+  fn run(&mut self, command: &str) {
+    if command.is_empty() {
+      return;
+    }
+
+    self.log.push(format!("> {command}"));
+
+    match command {
+      "clear" => self.log.clear(),
+      "help" => self.log.push(format!("commands: {}", COMMANDS.join(", "))),
+      "history" => self
+        .log
+        .extend(self.history.iter().map(|line| format!("  {line}"))),
+      "quit" => self.quit = true,
+      _ => self.log.push(format!("unknown command '{command}'")),
+    }
+
+    self.history.retain(|line| line != command);
+    self.history.push(command.to_string());
+  }
+}
+
+/// Tab completes commands, Up/Down walk through the history
+struct Callbacks<'a> {
+  history: &'a [String],
+  position: &'a mut Option<usize>,
+  log: &'a mut Vec<String>,
+}
+
+impl InputTextCallbackHandler for Callbacks<'_> {
+  fn on_completion(&mut self, mut data: TextCallbackData) {
+    let matches = COMMANDS
+      .iter()
+      .filter(|command| command.starts_with(data.str()))
+      .collect::<Vec<_>>();
+
+    match matches.as_slice() {
+      [] => {}
+      [command] => {
+        data.clear();
+        data.push_str(command);
+      }
+      _ => self.log.push(format!("possible: {matches:?}")),
+    }
+  }
+
+  fn on_history(&mut self, direction: HistoryDirection, mut data: TextCallbackData) {
+    if self.history.is_empty() {
+      return;
+    }
+
+    let next = match (direction, *self.position) {
+      (HistoryDirection::Up, None) => Some(self.history.len() - 1),
+      (HistoryDirection::Up, Some(position)) => Some(position.saturating_sub(1)),
+      (HistoryDirection::Down, Some(position)) if position + 1 < self.history.len() => {
+        Some(position + 1)
+      }
+      (HistoryDirection::Down, _) => None,
+    };
+
+    *self.position = next;
+    data.clear();
+    if let Some(next) = next {
+      data.push_str(&self.history[next]);
+    }
+  }
+}
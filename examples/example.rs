@@ -41,22 +41,25 @@ async fn _main() -> anyhow::Result<!> {
     },
   );
 
-  let noto_sans = ctx.add_font_family(noto_sans_family);
+  let noto_sans = ctx.add_font_family(noto_sans_family)?;
 
   ctx.set_default_font(noto_sans);
 
   ctx.setup(|ctx| {
     ctx.set_ini_filename(None);
   });
+  ctx.set_show_demo(true);
 
   let mut buf = String::new();
 
   let wait = Duration::from_millis(125);
   let mut zoom_wait = Instant::now() - wait;
   let mut font_size = 24f32;
+  // zooming only scales the text until ctrl is released, then the fonts are rebuilt once at the final size
+  let mut zoom_size = font_size;
 
   let texture = Texture2D::from_image(&gen_image(0.8));
-  let id = ctx.bind_texture_id(texture.raw_miniquad_id());
+  let id = ctx.bind_macroquad_texture(&texture);
 
   loop {
     let now = Instant::now();
@@ -70,30 +73,46 @@ async fn _main() -> anyhow::Result<!> {
       };
 
       if is_key_down(KeyCode::Minus) && now >= zoom_wait {
-        font_size /= multi;
-        font_size = font_size.floor();
+        zoom_size /= multi;
+        zoom_size = zoom_size.floor();
         zoom_wait = now + wait;
-        ctx.set_font_size(font_size);
+        ctx.set_font_global_scale(zoom_size / font_size);
       }
 
       if is_key_down(KeyCode::Equal) && now >= zoom_wait {
-        font_size *= multi;
-        font_size = font_size.floor();
+        zoom_size *= multi;
+        zoom_size = zoom_size.floor();
         zoom_wait = now + wait;
-        ctx.set_font_size(font_size);
+        ctx.set_font_global_scale(zoom_size / font_size);
       }
+    } else if zoom_size != font_size {
+      font_size = zoom_size;
+      ctx.set_font_global_scale(1.);
+      ctx.set_font_size(font_size);
     }
 
+    let mut anti_aliasing_changed = None;
+
     ctx.ui(|ctx, ui| {
-      ui.show_demo_window(&mut true);
       ui.window("Window")
         .size([900., 900.], Condition::FirstUseEver)
         .build(|| {
           ui.text("良い");
           ui.input_text("Input", &mut buf).build();
 
+          let mut anti_aliasing = ctx.anti_aliasing();
+          let mut changed = ui.checkbox("Anti-aliased lines", &mut anti_aliasing.lines);
+          changed |= ui.checkbox("Textured lines", &mut anti_aliasing.lines_use_tex);
+          changed |= ui.checkbox("Anti-aliased fill", &mut anti_aliasing.fill);
+
+          // textured lines may rebuild the font atlas, which can't happen during a frame
+          if changed {
+            anti_aliasing_changed = Some(anti_aliasing);
+          }
+
           for (handle, family) in ctx.get_fonts() {
-            let text = format!("[{:?}]: {}, {}", handle.id(), family.name(), family.size());
+            let size = ctx.effective_font_size(handle).unwrap_or(family.size());
+            let text = format!("[{:?}]: {}, {}", handle.id(), family.name(), size);
             ui.text(text);
           }
 
@@ -105,6 +124,10 @@ async fn _main() -> anyhow::Result<!> {
 
     ctx.draw();
 
+    if let Some(anti_aliasing) = anti_aliasing_changed {
+      ctx.set_anti_aliasing(anti_aliasing);
+    }
+
     next_frame().await;
   }
 }
@@ -0,0 +1,118 @@
+//! Using the crate with miniquad alone, without macroquad:
+//! `cargo run --example miniquad --no-default-features`
+//!
+//! The `macroquad` feature registers a macroquad input subscriber when the context is created,
+//! which needs macroquad to be running, so it has to be off here
+
+use miniquad::conf::Conf;
+use miniquad::{
+  EventHandler, KeyCode, KeyMods, MouseButton, RenderingBackend, TextureId, TouchPhase,
+};
+
+use imgui_macroquad::ImGuiContext;
+
+fn main() {
+  let conf = Conf {
+    window_title: "Miniquad".into(),
+    ..Default::default()
+  };
+
+  miniquad::start(conf, || Box::new(Stage::new()));
+}
+
+struct Stage {
+  // the context borrows the backend for as long as it lives, leaking it makes that the whole program
+  ctx: ImGuiContext<'static>,
+  texture: imgui::TextureId,
+}
+
+impl Stage {
+  fn new() -> Self {
+    let gl: &'static mut dyn RenderingBackend =
+      Box::leak(miniquad::window::new_rendering_backend());
+
+    // any texture of the backend can be bound, whether it's created by miniquad (like this one)
+    // or wraps a texture of the graphics API with `TextureId::from_raw_id`
+    let texture = checkerboard(gl);
+
+    let mut ctx = ImGuiContext::new(gl);
+    let texture = ctx.bind_texture_id(texture);
+
+    Self { ctx, texture }
+  }
+}
+
+fn checkerboard(gl: &mut dyn RenderingBackend) -> TextureId {
+  let size = 256;
+  let pixels = (0..size * size)
+    .flat_map(|i| {
+      let (x, y) = (i % size / 32, i / size / 32);
+      if (x + y) % 2 == 0 {
+        [230, 230, 230, 255]
+      } else {
+        [40, 120, 200, 255]
+      }
+    })
+    .collect::<Vec<u8>>();
+
+  gl.new_texture_from_rgba8(size as u16, size as u16, &pixels)
+}
+
+impl EventHandler for Stage {
+  fn update(&mut self) {}
+
+  fn draw(&mut self) {
+    let texture = self.texture;
+
+    self.ctx.ui(|_, ui| {
+      ui.window("Texture").build(|| {
+        ui.text("a texture created with miniquad");
+        imgui::Image::new(texture, [256., 256.]).build(ui);
+      });
+    });
+
+    self.ctx.draw_with_clear([0.16, 0.16, 0.16, 1.]);
+  }
+
+  // input has to be forwarded by hand without macroquad
+
+  fn mouse_motion_event(&mut self, x: f32, y: f32) {
+    self.ctx.mouse_motion_event(x, y);
+  }
+
+  fn mouse_wheel_event(&mut self, x: f32, y: f32) {
+    self.ctx.mouse_wheel_event(x, y);
+  }
+
+  fn mouse_button_down_event(&mut self, button: MouseButton, x: f32, y: f32) {
+    self.ctx.mouse_button_down_event(button, x, y);
+  }
+
+  fn mouse_button_up_event(&mut self, button: MouseButton, x: f32, y: f32) {
+    self.ctx.mouse_button_up_event(button, x, y);
+  }
+
+  fn char_event(&mut self, character: char, mods: KeyMods, repeat: bool) {
+    self.ctx.char_event(character, mods, repeat);
+  }
+
+  fn key_down_event(&mut self, keycode: KeyCode, mods: KeyMods, repeat: bool) {
+    self.ctx.key_down_event(keycode, mods, repeat);
+  }
+
+  fn key_up_event(&mut self, keycode: KeyCode, mods: KeyMods) {
+    self.ctx.key_up_event(keycode, mods);
+  }
+
+  fn touch_event(&mut self, phase: TouchPhase, id: u64, x: f32, y: f32) {
+    self.ctx.touch_event(phase, id, x, y);
+  }
+
+  fn window_minimized_event(&mut self) {
+    self.ctx.window_minimized_event();
+  }
+
+  fn window_restored_event(&mut self) {
+    self.ctx.window_restored_event();
+  }
+}
@@ -1,21 +1,27 @@
 // mod render;
 
 use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::ffi::c_void;
 use std::rc::Rc;
 
-use ::imgui::{FontConfig, FontId, FontSource};
+use ::imgui::{FontConfig, FontGlyphRanges, FontId, FontSource};
 use miniquad::window::screen_size;
 use miniquad::{
-  Bindings, BlendFactor, BlendState, BlendValue, BufferLayout, BufferSource, BufferType,
-  BufferUsage, Equation, EventHandler, KeyCode, KeyMods, MouseButton, PassAction, Pipeline,
-  PipelineParams, RawId, RenderingBackend, ShaderMeta, ShaderSource, TextureId, UniformBlockLayout,
-  UniformDesc, UniformType, UniformsSource, VertexAttribute, VertexFormat,
+  Backend, Bindings, BlendFactor, BlendState, BlendValue, BufferId, BufferLayout, BufferSource,
+  BufferType, BufferUsage, Comparison, Equation, EventHandler, FilterMode, KeyCode, KeyMods,
+  MipmapFilterMode, MouseButton, PassAction, Pipeline, PipelineParams, PrimitiveType,
+  RenderingBackend, ShaderMeta, ShaderSource, TextureFormat, TextureId, TextureParams, TouchPhase,
+  UniformBlockLayout, UniformDesc, UniformType, UniformsSource, VertexAttribute, VertexFormat,
 };
 
 #[cfg(feature = "macroquad")]
 #[allow(unused)]
 pub use feature_macroquad::*;
-use imgui::{DrawCmd, Io, Key, Ui};
+#[cfg(feature = "testing")]
+pub use feature_testing::*;
+use imgui::{Condition, DrawCmd, DrawListMut, Io, Key, Ui};
+use itertools::Itertools;
 
 /// reexport of imgui
 pub mod imgui {
@@ -25,11 +31,20 @@ pub mod imgui {
 mod shader {
   use super::*;
 
-  pub fn pipeline(ctx: &mut dyn RenderingBackend) -> Pipeline {
+  pub fn pipeline(
+    ctx: &mut dyn RenderingBackend,
+    alpha_discard: Option<f32>,
+    params: PipelineParams,
+  ) -> Pipeline {
+    let fragment = match alpha_discard {
+      Some(threshold) => fragment_alpha_discard(threshold),
+      None => FRAGMENT.to_string(),
+    };
+
     let shader = ctx
       .new_shader(
         ShaderSource::Glsl {
-          fragment: FRAGMENT,
+          fragment: &fragment,
           vertex: VERTEX,
         },
         meta(),
@@ -44,17 +59,24 @@ mod shader {
         VertexAttribute::new("color0", VertexFormat::Byte4),
       ],
       shader,
-      PipelineParams {
-        color_blend: Some(BlendState::new(
-          Equation::Add,
-          BlendFactor::Value(BlendValue::SourceAlpha),
-          BlendFactor::OneMinusValue(BlendValue::SourceAlpha),
-        )),
-        ..Default::default()
-      },
+      params,
     )
   }
 
+  pub fn default_params() -> PipelineParams {
+    PipelineParams {
+      // the UI always goes on top, whatever depth a previous 3D pass left behind
+      depth_test: Comparison::Always,
+      depth_write: false,
+      color_blend: Some(BlendState::new(
+        Equation::Add,
+        BlendFactor::Value(BlendValue::SourceAlpha),
+        BlendFactor::OneMinusValue(BlendValue::SourceAlpha),
+      )),
+      ..Default::default()
+    }
+  }
+
   pub const VERTEX: &str = r#"#version 100
     attribute vec2 position;
     attribute vec2 texcoord;
@@ -64,29 +86,53 @@ mod shader {
     varying lowp vec4 color;
 
     uniform mat4 Projection;
+    uniform mat4 Transform;
 
     void main() {
-        gl_Position = Projection * vec4(position, 0, 1);
+        gl_Position = Projection * Transform * vec4(position, 0, 1);
         gl_Position.z = 0.;
         color = color0 / 255.0;
         uv = texcoord;
     }"#;
 
+  /// Discards texels at or below `ALPHA_DISCARD` when it's defined, see [`fragment_alpha_discard`]
   pub const FRAGMENT: &str = r#"#version 100
     varying lowp vec4 color;
     varying lowp vec2 uv;
 
     uniform sampler2D Texture;
+    uniform lowp float Bgra;
 
     void main() {
-        gl_FragColor = color * texture2D(Texture, uv);
+        lowp vec4 texel = texture2D(Texture, uv);
+        gl_FragColor = color * (Bgra > 0.5 ? texel.bgra : texel);
+
+    #ifdef ALPHA_DISCARD
+        if (gl_FragColor.a <= ALPHA_DISCARD) {
+            discard;
+        }
+    #endif
     }"#;
 
+  /// [`FRAGMENT`] that discards texels with an alpha of `threshold` or below
+  pub fn fragment_alpha_discard(threshold: f32) -> String {
+    // #version has to stay the first line
+    FRAGMENT.replacen(
+      "#version 100\n",
+      &format!("#version 100\n#define ALPHA_DISCARD {threshold:.6}\n"),
+      1,
+    )
+  }
+
   pub fn meta() -> ShaderMeta {
     ShaderMeta {
       images: vec!["Texture".to_string()],
       uniforms: UniformBlockLayout {
-        uniforms: vec![UniformDesc::new("Projection", UniformType::Mat4)],
+        uniforms: vec![
+          UniformDesc::new("Projection", UniformType::Mat4),
+          UniformDesc::new("Transform", UniformType::Mat4),
+          UniformDesc::new("Bgra", UniformType::Float1),
+        ],
       },
     }
   }
@@ -95,25 +141,95 @@ mod shader {
   #[derive(Debug)]
   pub struct Uniforms {
     pub projection: glam::Mat4,
+    /// applied before the projection, see [`ImGuiContext::set_extra_transform`]
+    pub transform: glam::Mat4,
+    /// 1 to swap red and blue of the sampled texture, see [`ChannelOrder`]
+    pub bgra: f32,
   }
 }
 
+/// More than one context can exist at a time (e.g. one per render pass),
+/// methods that touch imgui's state (io, style, fonts) and the input event handlers
+/// make their context the current one first.
+/// Helpers taking a [`Ui`] act on the context of the frame it belongs to,
+/// and with several contexts call [`Self::make_current`] before dropping one
+/// or calling into `imgui::sys` directly
+///
+/// Tab and the arrow keys are forwarded as plain keys, an input text built with
+/// `.callback(InputTextCallback::COMPLETION | InputTextCallback::HISTORY, ..)` receives them while it's focused
+/// and everywhere else Tab moves focus as usual, see `examples/console.rs` for a console with completion and history
+///
+/// Typed characters outside the BMP (emoji, rare CJK) aren't received on Windows,
+/// miniquad drops them as UTF-16 surrogate halves before they become chars
 pub struct ImGuiContext<'a> {
   gl: &'a mut dyn RenderingBackend,
   font_texture: TextureId,
+  font_filter: FilterMode,
+  keep_atlas_pixels: bool,
+  atlas_pixels: Option<(Vec<u8>, (u32, u32))>,
   default_font: FontIdHandle,
   fonts: Vec<(FontIdHandle, FontFamily<'a>)>,
-  textures: Vec<(imgui::TextureId, TextureId)>,
+  textures: TextureRegistry,
   context: imgui::Context,
+  raw_context: *mut imgui::sys::ImGuiContext,
   last_frame: f64,
+  max_delta_time: f32,
+  delta_smoothing: usize,
+  recent_deltas: VecDeque<f32>,
+  scissor_enabled: bool,
+  scissor_origin: ScissorOrigin,
+  interactive: bool,
+  keyboard_enabled: bool,
+  focused: bool,
+  render_when_unfocused: bool,
+  projection: Option<glam::Mat4>,
+  display_size: Option<(f32, f32)>,
+  extra_transform: glam::Mat4,
+  window_layout: Vec<(String, [f32; 2], [f32; 2])>,
+  alpha_discard: Option<f32>,
+  last_geometry: (i32, i32),
+  last_draw_list_count: usize,
+  last_command_count: usize,
+  last_activity: f64,
+  frame_end_state: FrameEndState,
+  in_frame: bool,
+  pending_font_size: Option<f32>,
+  ui_scale: f32,
+  base_style: imgui::Style,
+  pending_ui_scale: Option<f32>,
+  pending_font_reload: bool,
+  frame_built: bool,
+  cache_last_frame: bool,
+  frame_checks: bool,
+  debug_wireframe: bool,
+  buffer_capacity_hint: (usize, usize),
+  vertex_buffers: Vec<StreamBuffer>,
+  index_buffers: Vec<StreamBuffer>,
+  show_demo: bool,
+  show_metrics: bool,
+  show_style_editor: bool,
+  atlas_rebuild_callback: Option<Box<dyn FnMut() + 'a>>,
+  raw_key_callback: Option<Box<dyn FnMut(KeyCode, bool) + 'a>>,
+  pipeline_params: PipelineParams,
+  pipeline: Pipeline,
+  touches: Vec<Touch>,
+  invert_scroll_h: bool,
+  invert_scroll_v: bool,
   #[cfg(feature = "macroquad")]
   mq_event_id: usize,
   #[cfg(feature = "macroquad")]
   mq_auto_trigger_event_handler: bool,
+  #[cfg(feature = "macroquad")]
+  mq_auto_flush: bool,
 }
 
 impl<'a> ImGuiContext<'a> {
   pub fn new(gl: &'a mut dyn RenderingBackend) -> Self {
+    // imgui-rs refuses to create a context while another one is current
+    unsafe {
+      imgui::sys::igSetCurrentContext(std::ptr::null_mut());
+    }
+
     let mut context = imgui::Context::create();
     let fonts = context.fonts();
 
@@ -123,7 +239,7 @@ impl<'a> ImGuiContext<'a> {
     let id = fonts.add_font(family.sources());
     let handle = FontIdHandle::new(id);
 
-    let fonts_list = vec![(handle.clone(), family)];
+    let fonts_list = vec![(handle, family)];
 
     let font_atlas = fonts.build_rgba32_texture();
     let font_texture = gl.new_texture_from_rgba8(
@@ -132,21 +248,184 @@ impl<'a> ImGuiContext<'a> {
       font_atlas.data,
     );
 
-    context.set_clipboard_backend(Clipboard);
+    context.set_clipboard_backend(Clipboard { enabled: true });
     set_keymap(context.io_mut());
+    context.io_mut().config_mac_os_behaviors = cfg!(target_os = "macos");
+
+    Self::from_raw(gl, context.suspend(), font_texture, fonts_list, None)
+  }
+
+  /// Routes every allocation imgui makes through `alloc`/`free` (given `user_data` on every call),
+  /// e.g. to track imgui's native memory in profiled builds.
+  ///
+  /// # Safety
+  /// It has to be set before any context is created (and not changed while one exists)
+  /// since memory allocated by one allocator would be freed by the other,
+  /// and the functions have to behave like `malloc`/`free`
+  pub unsafe fn set_allocator(
+    alloc: unsafe extern "C" fn(size: usize, user_data: *mut c_void) -> *mut c_void,
+    free: unsafe extern "C" fn(ptr: *mut c_void, user_data: *mut c_void),
+    user_data: *mut c_void,
+  ) {
+    imgui::sys::igSetAllocatorFunctions(Some(alloc), Some(free), user_data);
+  }
+
+  /// Rebuilds a context from the parts returned by [`Self::into_raw`],
+  /// the first font becomes the default font.
+  ///
+  /// `context` is activated, which makes it the current imgui context whichever one was current before
+  /// (`imgui::Context::suspend` turns a new context into one). `font_texture` has to belong to `gl`,
+  /// if the backend changed, create a new texture from the font atlas on the new one first
+  /// (activate the context, `fonts().build_rgba32_texture()`, then suspend it again).
+  ///
+  /// The context's style becomes the unscaled style (see [`Self::style`]) and the ui scale starts at 1 again,
+  /// [`Self::into_raw`] hands back the unscaled style so call [`Self::set_ui_scale`] again to keep a scale.
+  ///
+  /// `event_subscriber` is the macroquad input subscriber [`Self::into_raw`] gave back, `None` registers a new one.
+  /// macroquad can't unregister subscribers and keeps queueing events for every one of them,
+  /// so pass the old one back instead of registering one per round trip. Ignored without the `macroquad` feature
+  ///
+  /// # Panics
+  /// if `fonts` is empty
+  pub fn from_raw(
+    gl: &'a mut dyn RenderingBackend,
+    context: imgui::SuspendedContext,
+    font_texture: TextureId,
+    fonts: Vec<(FontIdHandle, FontFamily<'a>)>,
+    event_subscriber: Option<usize>,
+  ) -> Self {
+    #[cfg(not(feature = "macroquad"))]
+    let _ = event_subscriber;
+
+    let (mut context, raw_context) = make_context_current(context);
+    // with 16 bit indices a draw list over 65536 vertices (e.g. a big table whose clipping isn't set up)
+    // would overflow them, with this imgui starts over at a vertex offset instead
+    context
+      .io_mut()
+      .backend_flags
+      .insert(imgui::BackendFlags::RENDERER_HAS_VTX_OFFSET);
+    let default_font = fonts.first().expect("at least one font").0.clone();
+    let base_style = *context.style();
+    let pipeline = shader::pipeline(gl, None, shader::default_params());
 
     Self {
       gl,
       context,
+      raw_context,
       font_texture,
-      default_font: handle,
-      fonts: fonts_list,
-      textures: vec![],
+      font_filter: FilterMode::Linear,
+      keep_atlas_pixels: false,
+      atlas_pixels: None,
+      default_font,
+      fonts,
+      textures: TextureRegistry::new(),
       last_frame: miniquad::date::now(),
+      max_delta_time: 0.1,
+      delta_smoothing: 1,
+      recent_deltas: VecDeque::new(),
+      scissor_enabled: true,
+      scissor_origin: ScissorOrigin::TopLeft,
+      interactive: true,
+      keyboard_enabled: true,
+      focused: true,
+      render_when_unfocused: true,
+      projection: None,
+      display_size: None,
+      extra_transform: glam::Mat4::IDENTITY,
+      window_layout: vec![],
+      alpha_discard: None,
+      last_geometry: (0, 0),
+      last_draw_list_count: 0,
+      last_command_count: 0,
+      last_activity: miniquad::date::now(),
+      frame_end_state: FrameEndState::default(),
+      in_frame: false,
+      pending_font_size: None,
+      ui_scale: 1.0,
+      base_style,
+      pending_ui_scale: None,
+      pending_font_reload: false,
+      frame_built: false,
+      cache_last_frame: false,
+      frame_checks: false,
+      debug_wireframe: false,
+      buffer_capacity_hint: (0, 0),
+      vertex_buffers: vec![],
+      index_buffers: vec![],
+      show_demo: false,
+      show_metrics: false,
+      show_style_editor: false,
+      atlas_rebuild_callback: None,
+      raw_key_callback: None,
+      pipeline_params: shader::default_params(),
+      pipeline,
+      touches: vec![],
+      invert_scroll_h: false,
+      invert_scroll_v: false,
       #[cfg(feature = "macroquad")]
-      mq_event_id: macroquad::input::utils::register_input_subscriber(),
+      mq_event_id: event_subscriber
+        .unwrap_or_else(macroquad::input::utils::register_input_subscriber),
       #[cfg(feature = "macroquad")]
       mq_auto_trigger_event_handler: true,
+      #[cfg(feature = "macroquad")]
+      mq_auto_flush: true,
+    }
+  }
+
+  /// Splits the context into the suspended imgui context, the font texture, the font families
+  /// and the macroquad input subscriber (`None` without the `macroquad` feature) to hand back to [`Self::from_raw`].
+  ///
+  /// The font texture still lives on the backend this context was created with, the caller
+  /// now owns it and has to delete it (`RenderingBackend::delete_texture`) once it's not used,
+  /// bound textures aren't part of it and have to be bound again after [`Self::from_raw`].
+  ///
+  /// The imgui context gets the unscaled style back, the ui scale isn't part of the parts
+  pub fn into_raw(
+    mut self,
+  ) -> (
+    imgui::SuspendedContext,
+    TextureId,
+    Vec<(FontIdHandle, FontFamily<'a>)>,
+    Option<usize>,
+  ) {
+    self.make_current();
+    self.delete_buffers();
+
+    self.sync_base_style();
+    *self.context.style_mut() = self.base_style;
+
+    #[cfg(feature = "macroquad")]
+    let event_subscriber = Some(self.mq_event_id);
+    #[cfg(not(feature = "macroquad"))]
+    let event_subscriber = None;
+
+    (
+      self.context.suspend(),
+      self.font_texture,
+      self.fonts,
+      event_subscriber,
+    )
+  }
+
+  /// Deletes the GPU resources owned by the context and drops it,
+  /// call it before the backend goes away when contexts are created and dropped at runtime
+  /// since dropping the context on its own leaves them on the backend.
+  ///
+  /// Bound textures belong to the caller and are left alone, pipelines are kept
+  /// since miniquad can't delete them
+  pub fn destroy(mut self) {
+    self.make_current();
+    self.gl.delete_texture(self.font_texture);
+    self.delete_buffers();
+  }
+
+  fn delete_buffers(&mut self) {
+    for buffer in self
+      .vertex_buffers
+      .drain(..)
+      .chain(self.index_buffers.drain(..))
+    {
+      self.gl.delete_buffer(buffer.id);
     }
   }
 
@@ -154,37 +433,205 @@ impl<'a> ImGuiContext<'a> {
     self.fonts.iter()
   }
 
-  pub fn add_font_family(&mut self, family: FontFamily<'a>) -> FontIdHandle {
-    let fonts = self.context.fonts();
+  /// Size in pixels text of `handle`'s font is actually drawn at, its rasterized size in the atlas
+  /// times its own scale and [`Self::font_global_scale`], so it follows [`Self::set_ui_scale`] and any other scaling
+  /// unlike the configured [`FontFamily::size`]. `None` if the handle isn't in the atlas
+  pub fn effective_font_size(&self, handle: &FontIdHandle) -> Option<f32> {
+    self.make_current();
+
+    // `imgui::Context::fonts` needs a mutable borrow, reading the atlas doesn't
+    let atlas = unsafe { &*((*imgui::sys::igGetIO()).Fonts as *const imgui::FontAtlas) };
+    let font = atlas.get_font(handle.get())?;
+
+    Some(font.font_size * font.scale * self.context.io().font_global_scale)
+  }
+
+  /// Reports, per family, how many of the requested glyphs actually made it into the atlas.
+  /// Handy to find out why some text shows up as boxes.
+  ///
+  /// Glyphs aren't loaded on demand, the atlas only has the glyph ranges given when a family was added
+  /// and text outside them (e.g. pasted foreign text) shows the fallback glyph,
+  /// add a family with wider ranges up front instead
+  pub fn font_load_report(&self) -> Vec<FontLoadInfo> {
+    self.make_current();
+
+    use imgui::sys;
+
+    // `imgui::Context::fonts` needs a mutable borrow, reading the atlas doesn't
+    let raw_atlas = unsafe { (*sys::igGetIO()).Fonts };
+    let atlas = unsafe { &*(raw_atlas as *const imgui::FontAtlas) };
+
+    self
+      .fonts
+      .iter()
+      .filter_map(|(handle, family)| {
+        let font = atlas.get_font(handle.get())? as *const imgui::Font as *mut sys::ImFont;
+        let mut requested = vec![];
+
+        unsafe {
+          for i in 0..(*font).ConfigDataCount as usize {
+            let config = &*(*font).ConfigData.add(i);
+            let mut range = if config.GlyphRanges.is_null() {
+              sys::ImFontAtlas_GetGlyphRangesDefault(raw_atlas)
+            } else {
+              config.GlyphRanges
+            };
+
+            while *range != 0 {
+              requested.push((*range, *range.add(1)));
+              range = range.add(2);
+            }
+          }
+        }
+
+        // sources of the same family commonly overlap (e.g. both include latin)
+        let requested = requested
+          .into_iter()
+          .sorted()
+          .coalesce(|a, b| {
+            if b.0 <= a.1 + 1 {
+              Ok((a.0, a.1.max(b.1)))
+            } else {
+              Err((a, b))
+            }
+          })
+          .collect_vec();
+
+        let mut info = FontLoadInfo {
+          handle: handle.clone(),
+          name: family.name.clone(),
+          requested_glyphs: 0,
+          loaded_glyphs: 0,
+          missing_ranges: vec![],
+        };
+
+        for (start, end) in requested {
+          let loaded = (start..=end)
+            .filter(|c| unsafe { !sys::ImFont_FindGlyphNoFallback(font, *c).is_null() })
+            .count();
+
+          if loaded == 0 {
+            info.missing_ranges.push((start, end));
+          }
+
+          info.requested_glyphs += (end - start + 1) as usize;
+          info.loaded_glyphs += loaded;
+        }
+
+        Some(info)
+      })
+      .collect()
+  }
+
+  /// Adds a font family to the atlas, fails if the atlas would get larger than the GPU's
+  /// [max texture size](Self::max_texture_size), in which case the family isn't added.
+  ///
+  /// # Panics
+  /// during [`Self::ui`], the atlas can't change while a frame is built
+  pub fn add_font_family(
+    &mut self,
+    family: FontFamily<'a>,
+  ) -> Result<FontIdHandle, FontAtlasError> {
+    assert!(
+      !self.in_frame,
+      "add_font_family() can't be called during ui(), the font atlas is locked while a frame is built"
+    );
+    self.make_current();
+
+    let id = self
+      .context
+      .fonts()
+      .add_font(&family.scaled(self.ui_scale).sources);
+
+    if let Err(err) = self.check_font_atlas_size() {
+      // drops the new family from the atlas again
+      self.reload_fonts();
+
+      return Err(err);
+    }
 
-    let id = fonts.add_font(family.sources());
     let handle = FontIdHandle::new(id);
 
     self.fonts.push((handle.clone(), family));
 
-    let font_atlas = fonts.build_rgba32_texture();
+    self.rebuild_font_atlas();
 
-    self.gl.texture_resize(
-      self.font_texture,
-      font_atlas.width,
-      font_atlas.height,
-      Some(font_atlas.data),
-    );
+    Ok(handle)
+  }
+
+  /// Max width/height of a texture on this GPU, `None` if it can't be queried (non OpenGL backends)
+  pub fn max_texture_size(&self) -> Option<u32> {
+    if self.gl.info().backend != Backend::OpenGl {
+      return None;
+    }
+
+    let mut size = 0;
+
+    unsafe {
+      miniquad::gl::glGetIntegerv(miniquad::gl::GL_MAX_TEXTURE_SIZE, &mut size);
+    }
 
-    handle
+    Some(size as u32)
   }
 
-  pub fn set_font_size(&mut self, new_size: f32) {
+  fn check_font_atlas_size(&mut self) -> Result<(), FontAtlasError> {
+    let Some(max) = self.max_texture_size() else {
+      return Ok(());
+    };
+
+    let font_atlas = self.context.fonts().build_rgba32_texture();
+
+    if font_atlas.width > max || font_atlas.height > max {
+      return Err(FontAtlasError::TooLarge {
+        width: font_atlas.width,
+        height: font_atlas.height,
+        max,
+      });
+    }
+
+    Ok(())
+  }
+
+  /// Removes every font family, leaving only the default one which also becomes the default font.
+  ///
+  /// [`FontIdHandle`]s of the removed families are pointed at the default font so pushing them
+  /// doesn't panic, but `FontId`s taken out of them before (`handle.get()`) no longer point into the atlas
+  /// and imgui-rs panics when they're pushed.
+  ///
+  /// # Panics
+  /// during [`Self::ui`], the atlas can't change while a frame is built
+  pub fn clear_fonts(&mut self) {
+    assert!(
+      !self.in_frame,
+      "clear_fonts() can't be called during ui(), the font atlas is locked while a frame is built"
+    );
+    self.make_current();
+
     let fonts = self.context.fonts();
     fonts.clear();
 
-    for (handle, family) in self.fonts.iter_mut() {
-      family.update_size(new_size);
-      let id = fonts.add_font(family.sources());
-      handle.update(id);
+    let family = FontFamily::default();
+    let handle = FontIdHandle::new(fonts.add_font(&family.scaled(self.ui_scale).sources));
+
+    for (removed, _) in self.fonts.iter() {
+      removed.update(handle.get());
     }
 
-    let font_atlas = fonts.build_rgba32_texture();
+    self.fonts = vec![(handle.clone(), family)];
+    self.default_font = handle;
+
+    self.rebuild_font_atlas();
+  }
+
+  fn rebuild_font_atlas(&mut self) {
+    let font_atlas = self.context.fonts().build_rgba32_texture();
+
+    if self.keep_atlas_pixels {
+      self.atlas_pixels = Some((
+        font_atlas.data.to_vec(),
+        (font_atlas.width, font_atlas.height),
+      ));
+    }
 
     self.gl.texture_resize(
       self.font_texture,
@@ -193,261 +640,1627 @@ impl<'a> ImGuiContext<'a> {
       Some(font_atlas.data),
     );
 
-    self.context.style_mut().scale_all_sizes(1.0);
+    self.apply_fallback_chars();
+
+    if let Some(callback) = &mut self.atlas_rebuild_callback {
+      callback();
+    }
   }
 
-  pub fn set_default_font(&mut self, id: FontIdHandle) {
-    self.default_font = id;
+  /// Uploads the font atlas into a new texture and builds the UI's pipeline again, for after the GL context
+  /// was lost and restored (web, Android when the app is backgrounded) which takes every texture and shader with it.
+  ///
+  /// Textures bound with [`Self::bind_texture_id`] are the caller's, recreate them
+  /// and bind them again. With [`Self::keep_atlas_pixels`] the kept pixels are uploaded as they are
+  pub fn recreate_gpu_resources(&mut self) {
+    self.make_current();
+    self.rebuild_pipeline();
+    // the UI's buffers are gone with the context as well, they're created again on the next draw
+    self.vertex_buffers.clear();
+    self.index_buffers.clear();
+
+    // the old texture went away with the context, there's nothing left to delete
+    self.font_texture = match &self.atlas_pixels {
+      Some((data, (width, height))) => {
+        self
+          .gl
+          .new_texture_from_rgba8(*width as u16, *height as u16, data)
+      }
+      None => {
+        let font_atlas = self.context.fonts().build_rgba32_texture();
+
+        self.gl.new_texture_from_rgba8(
+          font_atlas.width as u16,
+          font_atlas.height as u16,
+          font_atlas.data,
+        )
+      }
+    };
+    self
+      .gl
+      .texture_set_filter(self.font_texture, self.font_filter, MipmapFilterMode::None);
   }
 
-  pub fn bind_texture_id(&mut self, id: TextureId) -> imgui::TextureId {
-    let id = to_imgui_id(id);
+  /// Keeps a copy of the font atlas' RGBA8 pixels around after every rebuild (off by default),
+  /// for [`Self::atlas_pixels`] and for [`Self::recreate_gpu_resources`] to skip going through the atlas again
+  pub fn keep_atlas_pixels(&mut self, keep: bool) {
+    self.make_current();
+
+    self.keep_atlas_pixels = keep;
+
+    if keep {
+      let font_atlas = self.context.fonts().build_rgba32_texture();
+      self.atlas_pixels = Some((
+        font_atlas.data.to_vec(),
+        (font_atlas.width, font_atlas.height),
+      ));
+    } else {
+      self.atlas_pixels = None;
+    }
+  }
 
-    self.textures.push(id);
+  /// RGBA8 pixels of the font atlas, row by row from the top,
+  /// only kept with [`Self::keep_atlas_pixels`]. Its size is [`Self::atlas_size`]
+  pub fn atlas_pixels(&self) -> Option<&[u8]> {
+    self.atlas_pixels.as_ref().map(|(data, _)| data.as_slice())
+  }
 
-    id.0
+  /// Width and height of [`Self::atlas_pixels`]
+  pub fn atlas_size(&self) -> Option<(u32, u32)> {
+    self.atlas_pixels.as_ref().map(|(_, size)| *size)
   }
 
-  #[cfg(feature = "macroquad")]
-  pub fn toggle_auto_trigger_event_handler(&mut self) {
-    self.mq_auto_trigger_event_handler = !self.mq_auto_trigger_event_handler;
+  /// Called after every rebuild of the font atlas (e.g. [`Self::set_font_size`], [`Self::add_font_family`]),
+  /// for invalidating anything that depends on the fonts. [`FontIdHandle`]s are already updated by then
+  pub fn set_atlas_rebuild_callback(&mut self, callback: impl FnMut() + 'a) {
+    self.atlas_rebuild_callback = Some(Box::new(callback));
   }
 
-  pub fn raw_imgui(&mut self) -> &mut imgui::Context {
-    &mut self.context
+  /// imgui picks the fallback glyph while building the atlas, so it's swapped afterwards
+  fn apply_fallback_chars(&mut self) {
+    use imgui::sys;
+
+    let atlas = self.context.fonts();
+
+    for (handle, family) in self.fonts.iter() {
+      let Some(fallback) = family.fallback_char else {
+        continue;
+      };
+
+      if let Some(font) = atlas.get_font(handle.get()) {
+        let font = font as *const imgui::Font as *mut sys::ImFont;
+
+        unsafe {
+          (*font).FallbackChar = fallback as sys::ImWchar;
+          sys::ImFont_BuildLookupTable(font);
+        }
+      }
+    }
   }
 
-  pub fn setup(&mut self, setup: impl FnOnce(&mut imgui::Context)) {
-    setup(&mut self.context);
+  /// [`Self::reload_fonts`], deferred to the start of the next frame during [`Self::ui`]
+  fn reload_fonts_after_frame(&mut self) {
+    if self.in_frame {
+      self.pending_font_reload = true;
+    } else {
+      self.reload_fonts();
+    }
   }
 
-  pub fn style(&mut self, style: impl FnOnce(&mut imgui::Style)) {
-    style(self.context.style_mut());
+  /// Adds every family again to a cleared atlas, updating their handles
+  fn reload_fonts(&mut self) {
+    let fonts = self.context.fonts();
+    fonts.clear();
+
+    for (handle, family) in self.fonts.iter() {
+      let id = fonts.add_font(&family.scaled(self.ui_scale).sources);
+      handle.update(id);
+    }
+
+    self.rebuild_font_atlas();
   }
 
-  pub fn ui(&mut self, frame: impl FnOnce(&mut Self, &Ui)) {
-    self.update();
+  /// Rebuilds every font at `new_size`, when called during [`Self::ui`]
+  /// the rebuild is deferred to the start of the next frame since the current one still uses the old fonts.
+  ///
+  /// Only text changes size, style metrics (padding, spacing, ...) stay as they are,
+  /// use [`Self::set_ui_scale`] to zoom the whole UI
+  pub fn set_font_size(&mut self, new_size: f32) {
+    self.make_current();
 
-    // fine since it's single threaded only,
-    // and I don't really want to do with RefCell/Mutex for performance reasons
-    let self_ = unsafe { ignore_lifetime_mut(self) };
+    if self.in_frame {
+      self.pending_font_size = Some(new_size);
+      return;
+    }
 
-    let ui = self.context.new_frame();
-    let stack = ui.push_font(self.default_font.get());
+    for (_, family) in self.fonts.iter_mut() {
+      family.update_size(new_size);
+    }
 
-    frame(self_, ui);
+    self.reload_fonts();
+  }
 
-    stack.end();
+  /// Scale applied to all text when drawing (1 by default), much cheaper than [`Self::set_font_size`]
+  /// since the atlas isn't rebuilt but text gets blurry the further it is from 1,
+  /// e.g. for previewing a zoom before rebuilding at the final size
+  pub fn set_font_global_scale(&mut self, scale: f32) {
+    self.make_current();
+
+    self.context.io_mut().font_global_scale = scale;
   }
 
-  fn update(&mut self) {
-    #[cfg(feature = "macroquad")]
-    if self.mq_auto_trigger_event_handler {
-      self.update_events();
-    }
+  pub fn font_global_scale(&self) -> f32 {
+    self.make_current();
 
-    let io = self.context.io_mut();
-    let now = miniquad::date::now();
+    self.context.io().font_global_scale
+  }
 
-    io.display_size = screen_size().into();
-    io.delta_time = (now - self.last_frame) as _;
-    self.last_frame = now;
+  /// Empty pixels imgui leaves around every glyph in the atlas, 1 by default, rebuilds the atlas
+  /// (at the start of the next frame when called during [`Self::ui`]).
+  /// With linear filtering, text drawn smaller than its rasterized size samples further than a pixel around
+  /// each glyph and picks up its neighbours ("faint lines around text") unless the padding covers that,
+  /// see [`Self::recommend_atlas_settings`]
+  pub fn set_font_atlas_padding(&mut self, pixels: u32) {
+    self.make_current();
+
+    self.context.fonts().tex_glyph_padding = pixels as i32;
+    self.reload_fonts_after_frame();
   }
 
-  pub fn draw(&mut self) {
-    #[cfg(feature = "macroquad")]
-    unsafe {
-      macroquad::window::get_internal_gl().flush();
+  /// Filtering of the font atlas texture, linear by default. Nearest is crisp when text is drawn
+  /// at the size it's rasterized at, but the baked anti-aliased lines
+  /// ([`AntiAliasingOptions::lines_use_tex`]) need linear filtering to look right
+  pub fn set_font_filter(&mut self, filter: FilterMode) {
+    self.font_filter = filter;
+    self
+      .gl
+      .texture_set_filter(self.font_texture, filter, MipmapFilterMode::None);
+  }
+
+  /// Atlas padding and filtering that avoid glyphs bleeding into each other for the fonts as they're drawn now.
+  ///
+  /// Text is only drawn at a size other than the one it's rasterized at through [`Self::font_global_scale`]
+  /// ([`Self::set_ui_scale`] and [`Self::set_font_size`] rebuild the atlas), at 1 nearest filtering can't bleed,
+  /// otherwise it's linear with enough padding for how far a scaled down glyph samples.
+  /// Always linear while the baked anti-aliased lines are used ([`AntiAliasingOptions::lines_use_tex`]).
+  /// Scales below 0.25 (and NaN) are treated as 0.25
+  pub fn recommend_atlas_settings(&self) -> AtlasSettings {
+    atlas_settings_for(self.font_global_scale(), self.anti_aliasing().lines_use_tex)
+  }
+
+  /// Scales the whole UI, fonts are rebuilt at their size times `scale` so text stays crisp
+  /// and style sizes (padding, spacing, rounding, ...) are scaled along with them.
+  /// Composes with [`Self::set_font_size`], which sets the size before scaling.
+  ///
+  /// Style sizes are always scaled from the unscaled style (see [`Self::style`]) rather than from the
+  /// previous scale, so going back and forth between scales doesn't drift.
+  ///
+  /// Like [`Self::set_font_size`], calling it during [`Self::ui`] defers it to the next frame
+  ///
+  /// # Panics
+  ///
+  /// If `scale` isn't a finite number above 0
+  pub fn set_ui_scale(&mut self, scale: f32) {
+    assert!(
+      scale.is_finite() && scale > 0.,
+      "ui scale must be finite and above 0, got {scale}"
+    );
+
+    self.make_current();
+
+    if self.in_frame {
+      self.pending_ui_scale = Some(scale);
+      return;
     }
 
-    let draw_data = self.context.render();
-    let pipeline = shader::pipeline(self.gl);
-    let (width, height) = screen_size();
+    self.sync_base_style();
+    self.ui_scale = scale;
+    self.apply_base_style();
 
-    let projection = glam::Mat4::orthographic_rh_gl(0., width, height, 0., -1., 1.);
-    let uniform = shader::Uniforms { projection };
+    self.reload_fonts();
+  }
 
-    self.gl.apply_pipeline(&pipeline);
-    self.gl.begin_default_pass(PassAction::Nothing);
+  pub fn ui_scale(&self) -> f32 {
+    self.ui_scale
+  }
 
-    let clip_off = draw_data.display_pos;
-    let clip_scale = draw_data.framebuffer_scale;
+  pub fn set_default_font(&mut self, id: FontIdHandle) {
+    self.default_font = id;
+  }
 
-    for draw_list in draw_data.draw_lists() {
-      let vtx_buffer = self.gl.new_buffer(
-        BufferType::VertexBuffer,
-        BufferUsage::Stream,
-        BufferSource::slice(draw_list.vtx_buffer()),
-      );
+  /// [`Self::set_default_font`] with the first family named `name`, returns false if there's none
+  pub fn set_default_font_by_name(&mut self, name: &str) -> bool {
+    let Some((handle, _)) = self.fonts.iter().find(|(_, family)| family.name == name) else {
+      return false;
+    };
 
-      let idx_buffer = self.gl.new_buffer(
-        BufferType::IndexBuffer,
-        BufferUsage::Stream,
-        BufferSource::slice(draw_list.idx_buffer()),
-      );
+    self.default_font = handle.clone();
 
-      let mut slice_start = 0;
-
-      for command in draw_list.commands() {
-        if let DrawCmd::Elements { count, cmd_params } = command {
-          let imgui::DrawCmdParams {
-            clip_rect,
-            texture_id,
-            ..
-          } = cmd_params;
-
-          let id = if texture_id.id() == 0 {
-            self.font_texture
-          } else {
-            let (_, id) = self
-              .textures
-              .iter()
-              .find(|(id, _)| *id == texture_id)
-              .copied()
-              .unwrap();
-
-            id
-          };
+    true
+  }
 
-          let bindings = Bindings {
-            vertex_buffers: vec![vtx_buffer],
-            index_buffer: idx_buffer,
-            images: vec![id],
-          };
+  /// Binds a miniquad texture so it can be used in imgui widgets,
+  /// ids are handed out by the context so they don't depend on miniquad internals.
+  ///
+  /// Binding an already bound texture returns the same id, so it's fine to call every frame.
+  ///
+  /// Works with any texture of the backend, created through miniquad or wrapping one of the graphics API
+  /// (`TextureId::from_raw_id`), see `examples/miniquad.rs` for using it without macroquad.
+  ///
+  /// The texture's pixels are assumed to be RGBA, see [`Self::bind_texture_id_with_order`]
+  pub fn bind_texture_id(&mut self, id: TextureId) -> imgui::TextureId {
+    self.bind_texture_id_with_order(id, ChannelOrder::Rgba)
+  }
 
-          let clip_rect = [
-            (clip_rect[0] - clip_off[0]) * clip_scale[0],
-            (clip_rect[1] - clip_off[1]) * clip_scale[1],
-            (clip_rect[2] - clip_off[0]) * clip_scale[0],
-            (clip_rect[3] - clip_off[1]) * clip_scale[1],
-          ];
-          let h = clip_rect[3] - clip_rect[1];
+  /// Same as [`Self::bind_texture_id`] for a texture whose pixels are in `order`,
+  /// red and blue are swapped when drawing a [`ChannelOrder::Bgra`] texture.
+  ///
+  /// Binding an already bound texture again changes its order
+  pub fn bind_texture_id_with_order(
+    &mut self,
+    id: TextureId,
+    order: ChannelOrder,
+  ) -> imgui::TextureId {
+    self.textures.bind(id, order)
+  }
 
-          self.gl.apply_scissor_rect(
-            clip_rect[0] as i32,
-            height as i32 - (clip_rect[1] + h) as i32,
-            (clip_rect[2] - clip_rect[0]) as i32,
-            h as i32,
-          );
+  /// Removes the binding of `id` so the context forgets about the texture, returns whether it was bound.
+  ///
+  /// The miniquad texture is still the caller's and isn't deleted, draw commands still using `id`
+  /// afterwards are skipped
+  pub fn unbind_texture_id(&mut self, id: imgui::TextureId) -> bool {
+    self.textures.unbind(id)
+  }
 
-          self.gl.apply_bindings(&bindings);
-          self.gl.apply_uniforms(UniformsSource::table(&uniform));
-          self.gl.draw(slice_start, count as i32, 1);
-          slice_start += count as i32;
-        }
-      }
-    }
+  /// Cmd based shortcuts and macOS text editing behavior, defaults to whether it's built for macOS
+  pub fn set_mac_osx_behaviors(&mut self, enabled: bool) {
+    self.make_current();
 
-    self.gl.end_render_pass();
+    self.context.io_mut().config_mac_os_behaviors = enabled;
   }
-}
 
-impl<'a> EventHandler for ImGuiContext<'a> {
-  fn update(&mut self) {}
+  /// When disabled, copying/pasting in imgui widgets doesn't touch the system clipboard
+  pub fn set_clipboard_enabled(&mut self, enabled: bool) {
+    self.make_current();
 
-  fn draw(&mut self) {}
+    self.context.set_clipboard_backend(Clipboard { enabled });
+  }
 
-  fn mouse_motion_event(&mut self, x: f32, y: f32) {
-    let io = self.context.io_mut();
-    io.mouse_pos = [x, y];
+  /// How often imgui autosaves its ini file after a change in seconds, 5 by default.
+  /// Only matters with an ini filename set, see [`Self::ini_dirty`] for saving manually
+  pub fn set_ini_saving_rate(&mut self, seconds: f32) {
+    self.make_current();
+
+    self.context.io_mut().ini_saving_rate = seconds;
   }
 
-  fn mouse_wheel_event(&mut self, x: f32, y: f32) {
-    let io = self.context.io_mut();
-    io.mouse_wheel = y / 100.;
-    io.mouse_wheel_h = x / 100.;
+  /// Max time between two clicks for them to count as a double click in seconds, 0.3 by default
+  pub fn set_double_click_time(&mut self, seconds: f32) {
+    self.make_current();
+
+    self.context.io_mut().mouse_double_click_time = seconds;
   }
 
-  fn mouse_button_down_event(&mut self, button: MouseButton, _x: f32, _y: f32) {
-    let io = self.context.io_mut();
-    let mouse_left = button == MouseButton::Left;
-    let mouse_right = button == MouseButton::Right;
-    let mouse_middle = button == MouseButton::Middle;
+  /// How far in pixels the mouse has to move while held before it counts as dragging, 6 by default.
+  /// Raising it helps on touchscreens where fingers wobble while tapping
+  pub fn set_drag_threshold(&mut self, pixels: f32) {
+    self.make_current();
 
-    io.mouse_down = [mouse_left, mouse_right, mouse_middle, false, false];
+    self.context.io_mut().mouse_drag_threshold = pixels;
   }
 
-  fn mouse_button_up_event(&mut self, _button: MouseButton, _x: f32, _y: f32) {
-    let io = self.context.io_mut();
-    io.mouse_down = [false, false, false, false, false];
+  /// With trickling (imgui's default) queued input is spread over frames, e.g. a press and release
+  /// of the same button within one frame still shows up as a click. Disabling it applies everything queued
+  /// in one frame, for scripted input (replaying with the `testing` feature's `EventPlayer`) to land deterministically.
+  ///
+  /// Only mouse and text input go through the queue, keys are read as they are at the start of the frame
+  pub fn set_trickle_events(&mut self, enabled: bool) {
+    self.make_current();
+
+    self.context.io_mut().config_input_trickle_event_queue = enabled;
   }
 
-  fn char_event(&mut self, character: char, mods: KeyMods, _: bool) {
-    let io = self.context.io_mut();
+  /// Upper bound of the frame delta given to imgui in seconds, 0.1 by default
+  pub fn set_max_delta_time(&mut self, max: f32) {
+    self.max_delta_time = max.max(f32::MIN_POSITIVE);
+  }
 
-    io.key_ctrl = mods.ctrl;
-    io.key_alt = mods.alt;
-    io.key_shift = mods.shift;
+  /// Gives imgui the average delta of the last `window` frames instead of the last one,
+  /// smoothing out animations (cursor blink, fades) when the frame rate is uneven.
+  /// 1 (or 0) is no smoothing, the default
+  pub fn set_delta_smoothing(&mut self, window: usize) {
+    self.delta_smoothing = window.max(1);
+  }
+
+  /// Updates a region of a bound texture in place, e.g. to stream video into an image
+  /// without reallocating the texture. `data` is RGBA8, so `width * height * 4` bytes.
+  ///
+  /// Returns false without updating anything if `id` isn't bound, the texture isn't RGBA8,
+  /// the region is empty or doesn't fit inside the texture, or `data` isn't the region's size.
+  /// Textures bound from a raw GL id (`TextureId::from_raw_id`) are always refused,
+  /// miniquad doesn't know their size
+  pub fn update_bound_texture_region(
+    &mut self,
+    id: imgui::TextureId,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    data: &[u8],
+  ) -> bool {
+    let Some((texture, _)) = self.textures.get(id) else {
+      return false;
+    };
+
+    if !texture_region_fits(
+      &self.gl.texture_params(texture),
+      x,
+      y,
+      width,
+      height,
+      data.len(),
+    ) {
+      return false;
+    }
+
+    self
+      .gl
+      .texture_update_part(texture, x, y, width, height, data);
 
-    io.add_input_character(character);
+    true
   }
 
-  fn key_down_event(&mut self, keycode: KeyCode, mods: KeyMods, _: bool) {
+  /// A non interactive UI is only displayed, input isn't forwarded to imgui
+  /// and there are no hover highlights or cursor changes
+  pub fn set_interactive(&mut self, interactive: bool) {
+    self.make_current();
+
+    use imgui::ConfigFlags;
+
+    self.interactive = interactive;
+
     let io = self.context.io_mut();
+    let flags = ConfigFlags::NO_MOUSE | ConfigFlags::NO_MOUSE_CURSOR_CHANGE;
 
-    // when the keycode is the modifier itself - mods.MODIFIER is false yet, however the modifier button is just pressed and is actually true
-    io.key_ctrl = mods.ctrl;
-    io.key_alt = mods.alt;
-    io.key_shift = mods.shift;
+    io.config_flags.set(flags, !interactive);
 
-    io.keys_down[keycode as usize] = true;
+    if !interactive {
+      // nothing would release what's held right now
+      for button in imgui::MouseButton::VARIANTS {
+        io.add_mouse_button_event(button, false);
+      }
+
+      io.keys_down.fill(false);
+      self.touches.clear();
+    }
   }
 
-  fn key_up_event(&mut self, keycode: KeyCode, mods: KeyMods) {
-    let io = self.context.io_mut();
+  /// Called with the miniquad keycode and whether it's pressed for every key event forwarded to imgui,
+  /// e.g. for a shortcut rebinding UI that needs the key itself rather than what imgui made of it
+  pub fn set_raw_key_callback(&mut self, callback: impl FnMut(KeyCode, bool) + 'a) {
+    self.raw_key_callback = Some(Box::new(callback));
+  }
 
-    // when the keycode is the modifier itself - mods.MODIFIER is true, however the modifier is actually released
-    io.key_ctrl = keycode != KeyCode::LeftControl && keycode != KeyCode::RightControl && mods.ctrl;
-    io.key_alt = keycode != KeyCode::LeftAlt && keycode != KeyCode::RightAlt && mods.alt;
-    io.key_shift = keycode != KeyCode::LeftShift && keycode != KeyCode::RightShift && mods.shift;
+  /// When disabled, drawing does nothing while the window is unfocused or minimized (enabled by default),
+  /// frames are still built and input still processed so the UI is up to date once it's back.
+  ///
+  /// miniquad reports it through `window_minimized_event`/`window_restored_event` (on focus changes on X11 and wasm),
+  /// macroquad doesn't pass those on, so this only works when they're forwarded to the context
+  pub fn set_render_when_unfocused(&mut self, enabled: bool) {
+    self.render_when_unfocused = enabled;
+  }
 
-    io.keys_down[keycode as usize] = false;
+  /// When disabled, keyboard and text input isn't forwarded to imgui, e.g. for touchscreen kiosks
+  /// with on-screen controls. Independent of [`Self::set_interactive`], both have to allow input for it to arrive.
+  ///
+  /// imgui 1.89 has no config flag for ignoring the keyboard (unlike `NO_MOUSE`), so it's only withheld
+  pub fn set_keyboard_enabled(&mut self, enabled: bool) {
+    self.make_current();
+
+    self.keyboard_enabled = enabled;
+
+    if !enabled {
+      // nothing would release what's held right now
+      let io = self.context.io_mut();
+      io.keys_down.fill(false);
+      io.key_ctrl = false;
+      io.key_shift = false;
+      io.key_alt = false;
+      io.key_super = false;
+    }
   }
-}
 
-/// Handle for FontId since resizing fonts will give new ids
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct FontIdHandle(Rc<RefCell<FontId>>);
+  /// Flips the direction of horizontal mouse wheel scrolling, for platforms or settings (natural scrolling)
+  /// where it ends up going the wrong way
+  pub fn set_invert_horizontal_scroll(&mut self, invert: bool) {
+    self.invert_scroll_h = invert;
+  }
 
-impl FontIdHandle {
-  fn new(id: FontId) -> Self {
-    Self(Rc::new(RefCell::new(id)))
+  /// Flips the direction of vertical mouse wheel scrolling, see [`Self::set_invert_horizontal_scroll`]
+  pub fn set_invert_vertical_scroll(&mut self, invert: bool) {
+    self.invert_scroll_v = invert;
   }
 
-  pub fn id(&self) -> *const () {
-    &self.get() as *const _ as _
+  /// Enables/disables clipping of draw commands with scissor rects (enabled by default),
+  /// when disabled widgets can be drawn outside of their windows
+  pub fn set_scissor_enabled(&mut self, enabled: bool) {
+    self.scissor_enabled = enabled;
   }
 
-  pub fn get(&self) -> FontId {
-    *self.0.borrow()
+  /// Which corner the target's y starts at, see [`ScissorOrigin`]
+  pub fn set_scissor_origin(&mut self, origin: ScissorOrigin) {
+    self.scissor_origin = origin;
   }
 
-  fn update(&self, id: FontId) {
-    *self.0.borrow_mut() = id;
+  /// Size of the window the context is driven by, `None` (the default) uses miniquad's `screen_size()`.
+  /// For contexts driven per window when running several miniquad windows,
+  /// imgui's display size, the projection and clipping all use it
+  pub fn set_display_size(&mut self, size: Option<(f32, f32)>) {
+    self.display_size = size;
   }
-}
 
-impl From<FontId> for FontIdHandle {
-  fn from(value: FontId) -> Self {
-    Self::new(value)
+  fn display_size(&self) -> (f32, f32) {
+    self.display_size.unwrap_or_else(screen_size)
   }
-}
 
-#[allow(clippy::from_over_into)]
-impl Into<FontId> for FontIdHandle {
-  fn into(self) -> FontId {
-    self.get()
+  /// Overrides the projection used for drawing, `None` goes back to the default
+  /// top-left origin orthographic projection of the screen.
+  ///
+  /// Scissor rects are still computed in window pixel space, so if the projection
+  /// moves the geometry around, clipping won't follow it, see [`Self::set_scissor_enabled`].
+  ///
+  /// The matrix is column major (`projection[column][row]`) so it doesn't tie callers to a `glam` version,
+  /// e.g. `Mat4::to_cols_array_2d` of whichever one they use
+  pub fn set_projection(&mut self, projection: Option<[[f32; 4]; 4]>) {
+    self.projection = projection.map(|projection| glam::Mat4::from_cols_array_2d(&projection));
   }
-}
 
-#[derive(Debug, Clone)]
-pub struct FontFamily<'a> {
-  name: String,
-  size_pixels: f32,
-  sources: Vec<FontSource<'a>>,
-}
+  /// Transform applied to the UI's vertices (in window pixels) before the projection, e.g. to shake
+  /// or slide in the whole UI without touching its layout, `None` goes back to no transform.
+  /// Column major like [`Self::set_projection`].
+  ///
+  /// Like with the projection, clipping stays where the untransformed UI is,
+  /// so anything moved out of its clip rects gets cut off, see [`Self::set_scissor_enabled`]
+  pub fn set_extra_transform(&mut self, transform: Option<[[f32; 4]; 4]>) {
+    self.extra_transform = transform
+      .map(|transform| glam::Mat4::from_cols_array_2d(&transform))
+      .unwrap_or(glam::Mat4::IDENTITY);
+  }
 
-impl Default for FontFamily<'static> {
+  /// Discards texels with an alpha at or below `threshold`, blending stays enabled.
+  /// Helps with fringes around text on GPUs with poor blending quality
+  pub fn set_alpha_discard(&mut self, threshold: Option<f32>) {
+    if self.alpha_discard != threshold {
+      self.alpha_discard = threshold;
+      self.rebuild_pipeline();
+    }
+  }
+
+  /// When enabled, drawing without building a new frame with [`Self::ui`] first
+  /// draws the last frame again instead of nothing (disabled by default),
+  /// so the UI can be built at a lower rate than the rest of the scene is drawn
+  pub fn set_cache_last_frame(&mut self, enabled: bool) {
+    self.cache_last_frame = enabled;
+  }
+
+  /// In debug builds, panics when [`Self::ui`] is called twice without drawing in between
+  /// or when drawing without a new frame (unless [`Self::set_cache_last_frame`] is enabled),
+  /// the usual reasons for a UI that never shows up. Does nothing in release builds
+  pub fn set_frame_checks(&mut self, enabled: bool) {
+    self.frame_checks = enabled;
+  }
+
+  /// Draws the edges of the UI's triangles instead of filling them, to see how dense the geometry is.
+  /// A debugging aid, the line list is built on the CPU every frame while it's enabled.
+  ///
+  /// Only takes effect in debug builds, release builds always fill the triangles
+  pub fn set_debug_wireframe(&mut self, enabled: bool) {
+    if cfg!(debug_assertions) && self.debug_wireframe != enabled {
+      self.debug_wireframe = enabled;
+      self.rebuild_pipeline();
+    }
+  }
+
+  /// Never on in release builds, so the wireframe paths are compiled out of them
+  fn debug_wireframe(&self) -> bool {
+    cfg!(debug_assertions) && self.debug_wireframe
+  }
+
+  /// Number of vertices and indices the UI's vertex/index buffers are created with room for at least,
+  /// for UIs known to have large draw lists (node editors, plots) so that room is there from the first frames.
+  /// None by default, buffers are sized by what's drawn.
+  ///
+  /// The buffers are kept and updated in place every frame, each only grows when a draw list doesn't fit
+  pub fn set_buffer_capacity_hint(&mut self, vertices: usize, indices: usize) {
+    self.buffer_capacity_hint = (vertices, indices);
+  }
+
+  /// Replaces the params the UI's pipeline is built with, every field is used as is
+  /// (blending, depth, culling, ...), only the vertex layout and shader stay the crate's own.
+  ///
+  /// Scissoring isn't part of them, see [`Self::set_scissor_enabled`]. Start from [`Self::pipeline_params`]
+  /// to keep the defaults: alpha blending, no depth test or writes
+  pub fn set_pipeline_params(&mut self, params: PipelineParams) {
+    self.pipeline_params = params;
+    self.rebuild_pipeline();
+  }
+
+  pub fn pipeline_params(&self) -> PipelineParams {
+    self.pipeline_params
+  }
+
+  /// The pipeline only changes with the settings above, so it's built when they do rather than every draw.
+  /// miniquad can't delete pipelines, the old one stays around
+  fn rebuild_pipeline(&mut self) {
+    let params = if self.debug_wireframe() {
+      PipelineParams {
+        primitive_type: PrimitiveType::Lines,
+        ..self.pipeline_params
+      }
+    } else {
+      self.pipeline_params
+    };
+
+    self.pipeline = shader::pipeline(self.gl, self.alpha_discard, params);
+  }
+
+  /// Whether imgui's ini settings (window positions, sizes, ...) changed since they were last saved,
+  /// for saving them manually with `imgui::Context::save_ini_settings` only when needed,
+  /// usually together with `set_ini_filename(None)`
+  pub fn ini_dirty(&self) -> bool {
+    self.make_current();
+
+    self.context.io().want_save_ini_settings
+  }
+
+  /// Marks the ini settings as saved, call after saving them following [`Self::ini_dirty`]
+  pub fn clear_ini_dirty(&mut self) {
+    self.make_current();
+
+    self.context.io_mut().want_save_ini_settings = false;
+  }
+
+  #[cfg(feature = "macroquad")]
+  pub fn toggle_auto_trigger_event_handler(&mut self) {
+    self.mq_auto_trigger_event_handler = !self.mq_auto_trigger_event_handler;
+  }
+
+  /// Whether [`Self::draw`] flushes macroquad's queued geometry first (enabled by default).
+  ///
+  /// When disabled, whatever macroquad still has queued is drawn when it's flushed later
+  /// (at the latest by `next_frame`), so on top of the UI unless it was already flushed
+  #[cfg(feature = "macroquad")]
+  pub fn set_auto_flush(&mut self, enabled: bool) {
+    self.mq_auto_flush = enabled;
+  }
+
+  /// Makes this the current imgui context, raw `imgui::sys` calls always act on
+  /// the current context so call this before them when more than one context exists
+  pub fn make_current(&self) {
+    make_raw_current(self.raw_context);
+  }
+
+  /// Makes the context current too, imgui-rs' io, style and font accessors act on the current context
+  pub fn raw_imgui(&mut self) -> &mut imgui::Context {
+    self.make_current();
+
+    &mut self.context
+  }
+
+  pub fn setup(&mut self, setup: impl FnOnce(&mut imgui::Context)) {
+    self.make_current();
+
+    setup(&mut self.context);
+  }
+
+  /// Edits the unscaled style, sizes are given at a ui scale of 1 and the edited style is
+  /// scaled by [`Self::ui_scale`] right after, the same goes for the other style setters.
+  ///
+  /// Edits made straight to imgui's style (through [`Self::raw_imgui`] or [`Self::setup`]) are
+  /// picked up while the ui scale is 1, at any other scale they're overwritten by the next style change
+  pub fn style(&mut self, style: impl FnOnce(&mut imgui::Style)) {
+    self.make_current();
+
+    self.sync_base_style();
+    style(&mut self.base_style);
+    self.apply_base_style();
+  }
+
+  /// Unscaled, see [`Self::style`]
+  pub fn set_window_rounding(&mut self, rounding: f32) {
+    self.style(|style| style.window_rounding = rounding);
+  }
+
+  /// Unscaled, see [`Self::style`]
+  pub fn set_frame_rounding(&mut self, rounding: f32) {
+    self.style(|style| style.frame_rounding = rounding);
+  }
+
+  /// Unscaled, see [`Self::style`]
+  pub fn set_item_spacing(&mut self, spacing: [f32; 2]) {
+    self.style(|style| style.item_spacing = spacing);
+  }
+
+  /// Unscaled, see [`Self::style`]
+  pub fn set_window_padding(&mut self, padding: [f32; 2]) {
+    self.style(|style| style.window_padding = padding);
+  }
+
+  /// At a ui scale of 1 imgui's style is the unscaled one, take any edit made to it directly
+  fn sync_base_style(&mut self) {
+    if self.ui_scale == 1. {
+      self.base_style = *self.context.style();
+    }
+  }
+
+  fn apply_base_style(&mut self) {
+    *self.context.style_mut() = scaled_style(self.base_style, self.ui_scale);
+  }
+
+  /// imgui's dark style with every color the preset draws in its blue recolored from `accent` (RGBA),
+  /// keeping the preset's alphas so hovered/active states stay as distinct as they are by default.
+  ///
+  /// Only colors are changed, sizes and rounding stay as they are
+  pub fn apply_themed_dark(&mut self, accent: [f32; 4]) {
+    use imgui::StyleColor;
+
+    self.style(|style| {
+      style.use_dark_colors();
+
+      let [r, g, b, a] = accent;
+      let color = |alpha: f32| [r, g, b, a * alpha];
+      // the preset's darker blue for title bars, tabs and frame backgrounds
+      let dark = |factor: f32, alpha: f32| [r * factor, g * factor, b * factor, a * alpha];
+
+      for (id, value) in [
+        (StyleColor::FrameBg, dark(0.5, 0.54)),
+        (StyleColor::FrameBgHovered, color(0.40)),
+        (StyleColor::FrameBgActive, color(0.67)),
+        (StyleColor::TitleBgActive, dark(0.5, 1.0)),
+        (StyleColor::CheckMark, color(1.0)),
+        (StyleColor::SliderGrab, dark(0.9, 1.0)),
+        (StyleColor::SliderGrabActive, color(1.0)),
+        (StyleColor::Button, color(0.40)),
+        (StyleColor::ButtonHovered, color(1.0)),
+        (StyleColor::ButtonActive, dark(0.9, 1.0)),
+        (StyleColor::Header, color(0.31)),
+        (StyleColor::HeaderHovered, color(0.80)),
+        (StyleColor::HeaderActive, color(1.0)),
+        (StyleColor::SeparatorHovered, dark(0.75, 0.78)),
+        (StyleColor::SeparatorActive, dark(0.75, 1.0)),
+        (StyleColor::ResizeGrip, color(0.20)),
+        (StyleColor::ResizeGripHovered, color(0.67)),
+        (StyleColor::ResizeGripActive, color(0.95)),
+        (StyleColor::Tab, dark(0.6, 0.86)),
+        (StyleColor::TabHovered, color(0.80)),
+        (StyleColor::TabActive, dark(0.7, 1.0)),
+        (StyleColor::TabUnfocusedActive, dark(0.45, 1.0)),
+        (StyleColor::TextSelectedBg, color(0.35)),
+        (StyleColor::NavHighlight, color(1.0)),
+      ] {
+        style[id] = value;
+      }
+    });
+  }
+
+  pub fn anti_aliasing(&self) -> AntiAliasingOptions {
+    self.make_current();
+
+    let style = self.context.style();
+
+    AntiAliasingOptions {
+      lines: style.anti_aliased_lines,
+      lines_use_tex: style.anti_aliased_lines_use_tex,
+      fill: style.anti_aliased_fill,
+    }
+  }
+
+  /// Turning these off makes for less geometry on low-end targets,
+  /// if `lines_use_tex` is on, the font atlas is rebuilt with baked lines when it doesn't have them,
+  /// during [`Self::ui`] that's deferred to the start of the next frame since the atlas can't change mid frame
+  pub fn set_anti_aliasing(&mut self, options: AntiAliasingOptions) {
+    self.style(|style| {
+      style.anti_aliased_lines = options.lines;
+      style.anti_aliased_lines_use_tex = options.lines_use_tex;
+      style.anti_aliased_fill = options.fill;
+    });
+
+    let fonts = self.context.fonts();
+
+    if options.lines_use_tex && fonts.flags.contains(imgui::FontAtlasFlags::NO_BAKED_LINES) {
+      fonts.flags.remove(imgui::FontAtlasFlags::NO_BAKED_LINES);
+      self.reload_fonts_after_frame();
+    }
+  }
+
+  pub fn ui(&mut self, frame: impl FnOnce(&mut Self, &Ui)) {
+    if cfg!(debug_assertions) && self.frame_checks {
+      assert!(
+        !self.frame_built,
+        "ui() was called again before the previous frame was drawn, call draw() once after every ui()"
+      );
+    }
+
+    self.make_current();
+    self.update();
+
+    // fine since it's single threaded only,
+    // and I don't really want to do with RefCell/Mutex for performance reasons
+    let self_ = unsafe { ignore_lifetime_mut(self) };
+
+    let ui = self.context.new_frame();
+    let stack = ui.push_font(self.default_font.get());
+
+    if self.show_demo {
+      ui.show_demo_window(&mut self.show_demo);
+    }
+
+    if self.show_metrics {
+      ui.show_metrics_window(&mut self.show_metrics);
+    }
+
+    if self.show_style_editor {
+      ui.window("Style Editor")
+        .opened(&mut self.show_style_editor)
+        .build(|| ui.show_default_style_editor());
+    }
+
+    let probe = cfg!(debug_assertions).then(id_stack_probe);
+
+    self.in_frame = true;
+    frame(self_, ui);
+    self.in_frame = false;
+
+    if let Some(probe) = probe {
+      assert_eq!(
+        id_stack_probe(),
+        probe,
+        "id stack isn't back to its baseline at the end of the frame, a push_id is missing its pop"
+      );
+    }
+
+    stack.end();
+
+    self.frame_end_state = FrameEndState::capture();
+    self.frame_built = true;
+  }
+
+  /// Shows imgui's demo window at the start of every [`Self::ui`] frame until it's closed
+  pub fn set_show_demo(&mut self, show: bool) {
+    self.show_demo = show;
+  }
+
+  /// Shows imgui's metrics/debugger window at the start of every [`Self::ui`] frame until it's closed
+  pub fn set_show_metrics(&mut self, show: bool) {
+    self.show_metrics = show;
+  }
+
+  /// Shows imgui's style editor at the start of every [`Self::ui`] frame until it's closed
+  pub fn set_show_style_editor(&mut self, show: bool) {
+    self.show_style_editor = show;
+  }
+
+  /// First use position and size of windows opened through [`Self::window`],
+  /// saves placing every window by hand when settings aren't persisted (`set_ini_filename(None)`)
+  pub fn set_default_window_layout(&mut self, layout: &[(&str, [f32; 2], [f32; 2])]) {
+    self.window_layout = layout
+      .iter()
+      .map(|(name, pos, size)| (name.to_string(), *pos, *size))
+      .collect();
+  }
+
+  /// `ui.window(name)` with the layout from [`Self::set_default_window_layout`] applied, if there is one
+  pub fn window<'ui, Label: AsRef<str>>(
+    &self,
+    ui: &'ui Ui,
+    name: Label,
+  ) -> imgui::Window<'ui, 'ui, Label> {
+    let layout = self
+      .window_layout
+      .iter()
+      .find(|(window, ..)| window == name.as_ref())
+      .map(|(_, pos, size)| (*pos, *size));
+
+    match layout {
+      Some((pos, size)) => ui
+        .window(name)
+        .position(pos, Condition::FirstUseEver)
+        .size(size, Condition::FirstUseEver),
+      None => ui.window(name),
+    }
+  }
+
+  /// [`Self::window`] with defaults for tool panels: it can't be collapsed
+  /// and gets a menu bar for `ui.begin_menu_bar()`, flags can still be changed with `.flags(..)`
+  pub fn panel_window<'ui, Label: AsRef<str>>(
+    &self,
+    ui: &'ui Ui,
+    title: Label,
+  ) -> imgui::Window<'ui, 'ui, Label> {
+    use imgui::WindowFlags;
+
+    self
+      .window(ui, title)
+      .flags(WindowFlags::NO_COLLAPSE | WindowFlags::MENU_BAR)
+  }
+
+  /// Builds a [`Self::panel_window`] with `f` as its body
+  pub fn panel(&self, ui: &Ui, title: &str, f: impl FnOnce()) {
+    self.panel_window(ui, title).build(f);
+  }
+
+  /// Position hint for the next window begun, for windows not built with `ui.window(..)`
+  /// (e.g. through `imgui::sys::igBegin`), `pivot` [0.5, 0.5] centers it on `pos`.
+  ///
+  /// Like the other `set_next_window_*` hints it needs `ui` since they only apply within a frame,
+  /// and is used up by the next window begun
+  pub fn set_next_window_pos(&self, _ui: &Ui, pos: [f32; 2], cond: Condition, pivot: [f32; 2]) {
+    unsafe {
+      imgui::sys::igSetNextWindowPos(pos.into(), cond as i32, pivot.into());
+    }
+  }
+
+  /// Size hint for the next window begun, 0 on an axis auto fits it
+  pub fn set_next_window_size(&self, _ui: &Ui, size: [f32; 2], cond: Condition) {
+    unsafe {
+      imgui::sys::igSetNextWindowSize(size.into(), cond as i32);
+    }
+  }
+
+  /// Size limits for the next window begun, -1 on an axis leaves it unconstrained
+  pub fn set_next_window_size_constraints(&self, _ui: &Ui, min: [f32; 2], max: [f32; 2]) {
+    unsafe {
+      imgui::sys::igSetNextWindowSizeConstraints(
+        min.into(),
+        max.into(),
+        None,
+        std::ptr::null_mut(),
+      );
+    }
+  }
+
+  /// Content size (without padding) of the next window begun, mostly for scrolling regions
+  pub fn set_next_window_content_size(&self, _ui: &Ui, size: [f32; 2]) {
+    unsafe {
+      imgui::sys::igSetNextWindowContentSize(size.into());
+    }
+  }
+
+  pub fn set_next_window_collapsed(&self, _ui: &Ui, collapsed: bool, cond: Condition) {
+    unsafe {
+      imgui::sys::igSetNextWindowCollapsed(collapsed, cond as i32);
+    }
+  }
+
+  /// Brings the next window begun to the front and focuses it
+  pub fn set_next_window_focus(&self, _ui: &Ui) {
+    unsafe {
+      imgui::sys::igSetNextWindowFocus();
+    }
+  }
+
+  /// Background alpha of the next window begun, overriding the style's window background color
+  pub fn set_next_window_bg_alpha(&self, _ui: &Ui, alpha: f32) {
+    unsafe {
+      imgui::sys::igSetNextWindowBgAlpha(alpha);
+    }
+  }
+
+  /// Conservative guess of whether the UI needs another frame, true for a short while after
+  /// any input or change in geometry and while a text input is active (cursor blink).
+  ///
+  /// Immediate mode makes exact detection impossible,
+  /// e.g. animations that only change colors without input aren't noticed
+  pub fn wants_redraw(&self) -> bool {
+    self.make_current();
+
+    self.context.io().want_text_input
+      || miniquad::date::now() - self.last_activity < REDRAW_GRACE_PERIOD
+  }
+
+  /// Number of draw lists in the last frame rendered by [`Self::draw`]
+  pub fn last_draw_list_count(&self) -> usize {
+    self.last_draw_list_count
+  }
+
+  /// Number of draw commands, over all draw lists, in the last frame rendered by [`Self::draw`]
+  pub fn last_command_count(&self) -> usize {
+    self.last_command_count
+  }
+
+  /// Number of vertices in the last rendered frame, as counted by imgui itself
+  pub fn render_vertices(&self) -> usize {
+    self.make_current();
+
+    self.context.io().metrics_render_vertices as usize
+  }
+
+  /// Number of indices in the last rendered frame, as counted by imgui itself
+  pub fn render_indices(&self) -> usize {
+    self.make_current();
+
+    self.context.io().metrics_render_indices as usize
+  }
+
+  /// Number of visible windows in the last rendered frame, as counted by imgui itself
+  pub fn render_windows(&self) -> usize {
+    self.make_current();
+
+    self.context.io().metrics_render_windows as usize
+  }
+
+  /// Whether a widget was hovered at the end of the last [`Self::ui`] frame,
+  /// e.g. to show the game's own tooltip instead
+  pub fn is_any_item_hovered(&self) -> bool {
+    self.frame_end_state.any_item_hovered
+  }
+
+  /// Whether keyboard/gamepad navigation is active, i.e. a window is focused and nav input goes to it,
+  /// as of the last [`Self::ui`] frame
+  pub fn nav_active(&self) -> bool {
+    self.make_current();
+
+    self.context.io().nav_active
+  }
+
+  /// Whether the navigation cursor (focus highlight) is shown, turns off again once the mouse is used,
+  /// for switching between mouse and controller prompts by what imgui actually does
+  pub fn nav_visible(&self) -> bool {
+    self.make_current();
+
+    self.context.io().nav_visible
+  }
+
+  /// Whether a widget was active (being clicked, dragged, typed in, ...)
+  /// at the end of the last [`Self::ui`] frame
+  pub fn is_any_item_active(&self) -> bool {
+    self.frame_end_state.any_item_active
+  }
+
+  /// Whether imgui saw `button` being dragged (moved past the drag threshold while held)
+  /// at the end of the last [`Self::ui`] frame, together with `io().want_capture_mouse`
+  /// it tells apart imgui dragging a window or widget from a drag meant for the game
+  pub fn is_mouse_dragging(&self, button: MouseButton) -> bool {
+    to_imgui_mouse_button(button)
+      .map(|button| self.frame_end_state.mouse_dragging[button as usize])
+      .unwrap_or(false)
+  }
+
+  /// Docks window `window` (its full label, `##` id included) into the dock node `dock_id`,
+  /// 0 undocks it. Also works before the window was ever built, then it's docked when it first shows up,
+  /// which ships a default layout without an ini file.
+  ///
+  /// `dock_id` is what `igDockSpace`/`igDockSpaceOverViewport` in [`imgui::sys`] were given or returned,
+  /// needs the `docking` feature
+  #[cfg(feature = "docking")]
+  pub fn dock_window_into(&self, window: &str, dock_id: u32) {
+    self.make_current();
+
+    let window = std::ffi::CString::new(window).expect("window names can't contain a nul byte");
+
+    unsafe {
+      imgui::sys::igDockBuilderDockWindow(window.as_ptr(), dock_id);
+    }
+  }
+
+  /// Whether any popup (modals, context menus, menus of a menu bar, combo boxes) was open
+  /// at the end of the last [`Self::ui`] frame, e.g. to pause the game while a dialog is up
+  pub fn any_popup_open(&self) -> bool {
+    self.frame_end_state.any_popup_open
+  }
+
+  /// Id of the widget hovered at the end of the last [`Self::ui`] frame,
+  /// needs the `docking` feature since only its bindings expose imgui internals
+  #[cfg(feature = "docking")]
+  pub fn hovered_id(&self) -> Option<u32> {
+    Some(self.frame_end_state.hovered_id).filter(|id| *id != 0)
+  }
+
+  /// Id of the widget active at the end of the last [`Self::ui`] frame,
+  /// needs the `docking` feature since only its bindings expose imgui internals
+  #[cfg(feature = "docking")]
+  pub fn active_id(&self) -> Option<u32> {
+    Some(self.frame_end_state.active_id).filter(|id| *id != 0)
+  }
+
+  fn mark_activity(&mut self) {
+    self.last_activity = miniquad::date::now();
+  }
+
+  /// Runs `f` with `id` pushed on the id stack, it's popped again even if `f` returns early
+  pub fn with_id<R>(&self, ui: &Ui, id: impl AsRef<str>, f: impl FnOnce() -> R) -> R {
+    let _token = ui.push_id(id);
+
+    f()
+  }
+
+  /// Size of `text` drawn with the font of `handle`, the font is only pushed for the measurement
+  pub fn calc_text_size(&self, ui: &Ui, handle: &FontIdHandle, text: impl AsRef<str>) -> [f32; 2] {
+    let _font = ui.push_font(handle.get());
+
+    ui.calc_text_size(text)
+  }
+
+  /// Size of `text` drawn with the font of `handle` when wrapped at `wrap_width` pixels (as `ui.text_wrapped` does
+  /// with the wrap width set), for laying out chat/log lines before drawing them
+  pub fn calc_wrapped_text_size(
+    &self,
+    ui: &Ui,
+    handle: &FontIdHandle,
+    text: impl AsRef<str>,
+    wrap_width: f32,
+  ) -> [f32; 2] {
+    let _font = ui.push_font(handle.get());
+
+    ui.calc_text_size_with_opts(text, false, wrap_width)
+  }
+
+  /// Read only child window `id` of `size` (0 fills the space, negative leaves that much free)
+  /// showing `lines` wrapped, it follows new lines while scrolled to the bottom
+  /// and stays put once scrolled up to read back
+  pub fn log_view<T: AsRef<str>>(
+    &self,
+    ui: &Ui,
+    id: impl AsRef<str>,
+    lines: impl IntoIterator<Item = T>,
+    size: [f32; 2],
+  ) {
+    ui.child_window(id).size(size).build(|| {
+      for line in lines {
+        ui.text_wrapped(line);
+      }
+
+      if ui.scroll_y() >= ui.scroll_max_y() {
+        ui.set_scroll_here_y_with_ratio(1.);
+      }
+    });
+  }
+
+  /// Size of the current window's id stack during a frame (starting at 1 for the window itself),
+  /// needs the `docking` feature since only its bindings expose imgui internals.
+  ///
+  /// In debug builds [`Self::ui`] asserts the id stack is back to where it started at the end of the frame
+  #[cfg(feature = "docking")]
+  pub fn id_stack_depth(&self) -> usize {
+    let window = unsafe { imgui::sys::igGetCurrentWindow() };
+
+    if window.is_null() {
+      return 0;
+    }
+
+    unsafe { (*window).IDStack.Size as usize }
+  }
+
+  /// Runs `f` with the draw list drawn on top of every window, for full screen overlays
+  /// (crosshairs, debug lines). Its clip rect covers the whole display so nothing gets scissored away
+  pub fn foreground<R>(&self, ui: &Ui, f: impl FnOnce(&DrawListMut) -> R) -> R {
+    f(&ui.get_foreground_draw_list())
+  }
+
+  /// Same as [`Self::foreground`] but drawn behind every window
+  pub fn background<R>(&self, ui: &Ui, f: impl FnOnce(&DrawListMut) -> R) -> R {
+    f(&ui.get_background_draw_list())
+  }
+
+  /// Runs `f` with a clip rect (screen coordinates, like everything drawn on `draw_list`) pushed on `draw_list`,
+  /// `intersect` clips it to the current clip rect (usually the window) rather than replacing it.
+  ///
+  /// It's turned into a scissor rect like every other clip rect when drawing,
+  /// so content is only clipped once, to exactly that region
+  pub fn with_clip_rect<R>(
+    &self,
+    draw_list: &DrawListMut,
+    rect_min: [f32; 2],
+    rect_max: [f32; 2],
+    intersect: bool,
+    f: impl FnOnce() -> R,
+  ) -> R {
+    let mut result = None;
+    let f = || result = Some(f());
+
+    if intersect {
+      draw_list.with_clip_rect_intersect(rect_min, rect_max, f);
+    } else {
+      draw_list.with_clip_rect(rect_min, rect_max, f);
+    }
+
+    result.expect("the clip rect closure always runs")
+  }
+
+  /// Draws the texture `id` (of `tex_size`) as large as fits in `avail` without distorting it,
+  /// centered with the leftover space on either side, e.g. `avail` of `ui.content_region_avail()`
+  pub fn image_fit(&self, ui: &Ui, id: imgui::TextureId, tex_size: [f32; 2], avail: [f32; 2]) {
+    let scale = (avail[0] / tex_size[0]).min(avail[1] / tex_size[1]).max(0.);
+    let size = [tex_size[0] * scale, tex_size[1] * scale];
+
+    let [x, y] = ui.cursor_pos();
+    ui.set_cursor_pos([x + (avail[0] - size[0]) / 2., y + (avail[1] - size[1]) / 2.]);
+
+    imgui::Image::new(id, size).build(ui);
+  }
+
+  fn update(&mut self) {
+    if let Some(size) = self.pending_font_size.take() {
+      self.set_font_size(size);
+    }
+
+    if let Some(scale) = self.pending_ui_scale.take() {
+      self.set_ui_scale(scale);
+    }
+
+    if std::mem::take(&mut self.pending_font_reload) {
+      self.reload_fonts();
+    }
+
+    #[cfg(feature = "macroquad")]
+    if self.mq_auto_trigger_event_handler {
+      self.update_events();
+    }
+
+    let (width, height) = self.display_size();
+    let io = self.context.io_mut();
+    let now = miniquad::date::now();
+
+    io.display_size = next_display_size(io.display_size, (width, height));
+
+    // long pauses (breakpoints, minimized window) would make animations jump,
+    // and imgui needs a positive delta even if the clock goes backwards
+    let delta = ((now - self.last_frame) as f32).clamp(f32::MIN_POSITIVE, self.max_delta_time);
+    self.last_frame = now;
+
+    self.recent_deltas.push_back(delta);
+    while self.recent_deltas.len() > self.delta_smoothing.max(1) {
+      self.recent_deltas.pop_front();
+    }
+
+    io.delta_time = self.recent_deltas.iter().sum::<f32>() / self.recent_deltas.len() as f32;
+  }
+
+  /// Renders the last frame built with [`Self::ui`].
+  ///
+  /// With the `macroquad` feature, macroquad's queued geometry is flushed first so the UI
+  /// ends up on top of it (see [`Self::set_auto_flush`]), call this after all of the frame's macroquad drawing
+  /// and right before `next_frame().await`
+  pub fn draw(&mut self) {
+    #[cfg(feature = "macroquad")]
+    if self.mq_auto_flush {
+      unsafe {
+        macroquad::window::get_internal_gl().flush();
+      }
+    }
+
+    self.draw_unflushed();
+  }
+
+  /// Same as [`Self::draw`] without flushing macroquad first,
+  /// for when the flush is already handled elsewhere.
+  ///
+  /// The UI is drawn into the default framebuffer, so it's multisampled like the rest of
+  /// the scene whenever the window was created with `Conf::sample_count` above 1,
+  /// miniquad pipelines have no sample count of their own to match
+  pub fn draw_unflushed(&mut self) {
+    self.render_frame(Some(PassAction::Nothing), self.display_size());
+  }
+
+  /// Same as [`Self::draw_unflushed`] with the screen cleared to `color` (rgba) first,
+  /// for apps where the UI is the only thing drawn
+  pub fn draw_with_clear(&mut self, color: [f32; 4]) {
+    let [r, g, b, a] = color;
+
+    self.render_frame(
+      Some(PassAction::clear_color(r, g, b, a)),
+      self.display_size(),
+    );
+  }
+
+  /// Draws into the render pass that's already active instead of beginning/ending the default one,
+  /// for renderers that are mid-pass. Nothing is flushed, even with the `macroquad` feature.
+  ///
+  /// The pass is expected to cover the screen since clipping is computed from its size (see [`Self::set_display_size`]),
+  /// use [`Self::draw_inline_sized`] for passes into a target of another size.
+  /// Afterwards the UI's pipeline, bindings and last scissor rect are still applied,
+  /// so re-apply your own before drawing anything else in the pass
+  pub fn draw_inline(&mut self) {
+    self.render_frame(None, self.display_size());
+  }
+
+  /// Same as [`Self::draw_inline`] for a pass whose target is `render_target_size` (width, height) in pixels,
+  /// the projection and the scissor's y-flip use it instead of `screen_size()`
+  pub fn draw_inline_sized(&mut self, render_target_size: (f32, f32)) {
+    self.render_frame(None, render_target_size);
+  }
+
+  /// Draws the frame into the default pass begun with `pass_action`, or into the active pass if `None`
+  fn render_frame(&mut self, pass_action: Option<PassAction>, (width, height): (f32, f32)) {
+    self.make_current();
+
+    let wireframe = self.debug_wireframe();
+
+    let draw_data = if std::mem::take(&mut self.frame_built) {
+      self.context.render()
+    } else if self.cache_last_frame {
+      // imgui keeps the last rendered draw data around until the next frame is started
+      match unsafe {
+        imgui::sys::igGetDrawData()
+          .cast::<imgui::DrawData>()
+          .as_ref()
+      } {
+        Some(draw_data) => draw_data,
+        None => return,
+      }
+    } else {
+      if cfg!(debug_assertions) && self.frame_checks {
+        panic!(
+          "draw() was called without building a frame with ui() first (or twice for the same frame), \
+           call ui() once before every draw() or enable set_cache_last_frame"
+        );
+      }
+
+      // rendering without a frame would fail imgui's assertions
+      return;
+    };
+
+    let geometry = (draw_data.total_vtx_count, draw_data.total_idx_count);
+    if geometry != self.last_geometry {
+      self.last_geometry = geometry;
+      self.last_activity = miniquad::date::now();
+    }
+
+    self.last_draw_list_count = draw_data.draw_lists_count();
+    self.last_command_count = draw_data
+      .draw_lists()
+      .map(|draw_list| draw_list.commands().count())
+      .sum();
+
+    // nothing is visible and the projection would divide by zero
+    if width <= 0. || height <= 0. {
+      return;
+    }
+
+    if !self.focused && !self.render_when_unfocused {
+      return;
+    }
+
+    // every triangle becomes its 3 edges
+    let indices_per_index = if wireframe { 2 } else { 1 };
+
+    let projection = self
+      .projection
+      .unwrap_or_else(|| glam::Mat4::orthographic_rh_gl(0., width, height, 0., -1., 1.));
+
+    self.gl.apply_pipeline(&self.pipeline);
+
+    let own_pass = pass_action.is_some();
+    if let Some(pass_action) = pass_action {
+      self.gl.begin_default_pass(pass_action);
+    }
+
+    let clip_off = draw_data.display_pos;
+    let clip_scale = draw_data.framebuffer_scale;
+
+    // a draw list needs another vertex buffer for every vertex offset, so they're counted separately
+    let mut vertex_slot = 0;
+
+    for (index_slot, draw_list) in draw_data.draw_lists().enumerate() {
+      let mut vtx_buffer = stream_buffer(
+        self.gl,
+        &mut self.vertex_buffers,
+        vertex_slot,
+        BufferType::VertexBuffer,
+        draw_list.vtx_buffer(),
+        self.buffer_capacity_hint.0,
+      );
+      vertex_slot += 1;
+
+      let wireframe_indices;
+      let indices = if wireframe {
+        wireframe_indices = triangle_edges(draw_list.idx_buffer());
+        wireframe_indices.as_slice()
+      } else {
+        draw_list.idx_buffer()
+      };
+
+      let idx_buffer = stream_buffer(
+        self.gl,
+        &mut self.index_buffers,
+        index_slot,
+        BufferType::IndexBuffer,
+        indices,
+        self.buffer_capacity_hint.1,
+      );
+
+      for step in draw_steps(draw_list, indices_per_index) {
+        // miniquad can't draw from a base vertex, so the vertices past the offset get their own buffer,
+        // the index type (u16 or u32, whichever imgui was built with) comes from the index buffer's element size
+        if step.new_vertex_buffer {
+          vtx_buffer = stream_buffer(
+            self.gl,
+            &mut self.vertex_buffers,
+            vertex_slot,
+            BufferType::VertexBuffer,
+            &draw_list.vtx_buffer()[step.vtx_offset..],
+            self.buffer_capacity_hint.0,
+          );
+          vertex_slot += 1;
+        }
+
+        let (id, order) = if step.texture_id.id() == 0 {
+          (self.font_texture, ChannelOrder::Rgba)
+        } else {
+          match self.textures.get(step.texture_id) {
+            Some(texture) => texture,
+            // unbound while the UI still draws it (e.g. a cached frame), nothing to sample
+            None => continue,
+          }
+        };
+
+        let uniform = shader::Uniforms {
+          projection,
+          transform: self.extra_transform,
+          bgra: (order == ChannelOrder::Bgra) as u8 as f32,
+        };
+
+        let bindings = Bindings {
+          vertex_buffers: vec![vtx_buffer],
+          index_buffer: idx_buffer,
+          images: vec![id],
+        };
+
+        if self.scissor_enabled {
+          let clip_rect = step.clip_rect;
+          let clip_rect = [
+            (clip_rect[0] - clip_off[0]) * clip_scale[0],
+            (clip_rect[1] - clip_off[1]) * clip_scale[1],
+            (clip_rect[2] - clip_off[0]) * clip_scale[0],
+            (clip_rect[3] - clip_off[1]) * clip_scale[1],
+          ];
+          let (x, y, w, h) = match self.scissor_origin {
+            ScissorOrigin::TopLeft => scissor_rect(clip_rect, height),
+            ScissorOrigin::BottomLeft => {
+              let (x, _, w, h) = scissor_rect(clip_rect, height);
+              (x, clip_rect[1].floor() as i32, w, h)
+            }
+          };
+
+          self.gl.apply_scissor_rect(x, y, w, h);
+        }
+
+        self.gl.apply_bindings(&bindings);
+        self.gl.apply_uniforms(UniformsSource::table(&uniform));
+        self
+          .gl
+          .draw(step.index_start as i32, step.index_count as i32, 1);
+      }
+    }
+
+    if own_pass {
+      self.gl.end_render_pass();
+    }
+  }
+}
+
+impl<'a> EventHandler for ImGuiContext<'a> {
+  fn update(&mut self) {}
+
+  fn draw(&mut self) {}
+
+  fn window_minimized_event(&mut self) {
+    self.focused = false;
+  }
+
+  fn window_restored_event(&mut self) {
+    self.focused = true;
+    // repaints right away for apps idling on wants_redraw
+    self.mark_activity();
+  }
+
+  fn mouse_motion_event(&mut self, x: f32, y: f32) {
+    if !self.interactive {
+      return;
+    }
+
+    self.make_current();
+    self.mark_activity();
+
+    let io = self.context.io_mut();
+    io.add_mouse_pos_event([x, y]);
+  }
+
+  fn mouse_wheel_event(&mut self, x: f32, y: f32) {
+    if !self.interactive {
+      return;
+    }
+
+    self.make_current();
+    self.mark_activity();
+
+    let x = if self.invert_scroll_h { -x } else { x };
+    let y = if self.invert_scroll_v { -y } else { y };
+
+    let io = self.context.io_mut();
+    io.add_mouse_wheel_event([x / 100., y / 100.]);
+  }
+
+  fn mouse_button_down_event(&mut self, button: MouseButton, _x: f32, _y: f32) {
+    if !self.interactive {
+      return;
+    }
+
+    self.make_current();
+    self.mark_activity();
+
+    apply_mouse_button_event(self.context.io_mut(), button, true);
+  }
+
+  fn mouse_button_up_event(&mut self, button: MouseButton, _x: f32, _y: f32) {
+    if !self.interactive {
+      return;
+    }
+
+    self.make_current();
+    self.mark_activity();
+
+    apply_mouse_button_event(self.context.io_mut(), button, false);
+  }
+
+  /// A single touch drives the cursor like the left mouse button,
+  /// two touches scroll once their latest movements point the same way (positive dot product),
+  /// by half of each finger's movement so the content follows the pair's average.
+  /// Touches moving towards or away from each other (a pinch) or staying put (a two finger tap) don't scroll.
+  ///
+  /// The second touch releases the first one's press so it doesn't drag anything along,
+  /// with macroquad turn off `simulate_mouse_with_touch` so touches don't also arrive as mouse events
+  fn touch_event(&mut self, phase: TouchPhase, id: u64, x: f32, y: f32) {
+    if !self.interactive {
+      return;
+    }
+
+    self.make_current();
+    self.mark_activity();
+
+    let io = self.context.io_mut();
+
+    match phase {
+      TouchPhase::Started => {
+        self.touches.push(Touch {
+          id,
+          position: [x, y],
+          delta: [0., 0.],
+        });
+
+        match self.touches.len() {
+          1 => {
+            io.add_mouse_pos_event([x, y]);
+            io.add_mouse_button_event(imgui::MouseButton::Left, true);
+          }
+          2 => io.add_mouse_button_event(imgui::MouseButton::Left, false),
+          _ => {}
+        }
+      }
+      TouchPhase::Moved => {
+        let Some(index) = self.touches.iter().position(|touch| touch.id == id) else {
+          return;
+        };
+
+        let touch = &mut self.touches[index];
+        let delta = [x - touch.position[0], y - touch.position[1]];
+        touch.position = [x, y];
+        touch.delta = delta;
+
+        match self.touches.as_slice() {
+          [_] => io.add_mouse_pos_event([x, y]),
+          [first, second] => {
+            let other = if index == 0 { second } else { first };
+            let parallel = delta[0] * other.delta[0] + delta[1] * other.delta[1] > 0.;
+
+            if parallel {
+              io.add_mouse_wheel_event([
+                delta[0] / 2. / TOUCH_SCROLL_PIXELS_PER_WHEEL,
+                delta[1] / 2. / TOUCH_SCROLL_PIXELS_PER_WHEEL,
+              ]);
+            }
+          }
+          _ => {}
+        }
+      }
+      TouchPhase::Ended | TouchPhase::Cancelled => {
+        self.touches.retain(|touch| touch.id != id);
+
+        if self.touches.is_empty() {
+          io.add_mouse_pos_event([x, y]);
+          io.add_mouse_button_event(imgui::MouseButton::Left, false);
+        }
+      }
+    }
+  }
+
+  fn char_event(&mut self, character: char, _: KeyMods, _: bool) {
+    if !self.interactive || !self.keyboard_enabled {
+      return;
+    }
+
+    self.make_current();
+    self.mark_activity();
+
+    apply_char_event(self.context.io_mut(), character);
+  }
+
+  fn key_down_event(&mut self, keycode: KeyCode, mods: KeyMods, _: bool) {
+    if !self.interactive || !self.keyboard_enabled {
+      return;
+    }
+
+    self.make_current();
+    self.mark_activity();
+
+    if let Some(callback) = &mut self.raw_key_callback {
+      callback(keycode, true);
+    }
+
+    apply_key_event(self.context.io_mut(), keycode, mods, true);
+  }
+
+  fn key_up_event(&mut self, keycode: KeyCode, mods: KeyMods) {
+    if !self.interactive || !self.keyboard_enabled {
+      return;
+    }
+
+    self.make_current();
+    self.mark_activity();
+
+    if let Some(callback) = &mut self.raw_key_callback {
+      callback(keycode, false);
+    }
+
+    apply_key_event(self.context.io_mut(), keycode, mods, false);
+  }
+}
+
+/// Handle for FontId since resizing fonts will give new ids
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FontIdHandle(Rc<RefCell<FontId>>);
+
+impl FontIdHandle {
+  fn new(id: FontId) -> Self {
+    Self(Rc::new(RefCell::new(id)))
+  }
+
+  pub fn id(&self) -> *const () {
+    &self.get() as *const _ as _
+  }
+
+  pub fn get(&self) -> FontId {
+    *self.0.borrow()
+  }
+
+  fn update(&self, id: FontId) {
+    *self.0.borrow_mut() = id;
+  }
+}
+
+impl From<FontId> for FontIdHandle {
+  fn from(value: FontId) -> Self {
+    Self::new(value)
+  }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<FontId> for FontIdHandle {
+  fn into(self) -> FontId {
+    self.get()
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct FontFamily<'a> {
+  name: String,
+  size_pixels: f32,
+  sources: Vec<FontSource<'a>>,
+  fallback_char: Option<char>,
+  rasterizer_multiply: Option<f32>,
+}
+
+impl Default for FontFamily<'static> {
   fn default() -> Self {
     Self {
       name: "ProggyClean.ttf".into(),
@@ -458,6 +2271,8 @@ impl Default for FontFamily<'static> {
           ..Default::default()
         }),
       }],
+      fallback_char: None,
+      rasterizer_multiply: None,
     }
   }
 }
@@ -468,7 +2283,56 @@ impl<'a> FontFamily<'a> {
       name: name.to_string(),
       size_pixels: size,
       sources: vec![],
+      fallback_char: None,
+      rasterizer_multiply: None,
+    }
+  }
+
+  /// The default font (ProggyClean) with `data` merged into it, e.g. an icon font
+  /// for the `glyph_ranges` it covers, without having to ship a base font
+  pub fn default_with_merge(data: &'a [u8], glyph_ranges: FontGlyphRanges) -> Self {
+    let mut family = Self {
+      sources: FontFamily::default().sources,
+      ..Self::new("ProggyClean.ttf", 13.0)
+    };
+
+    family.add_font_from_bytes_ex(
+      data,
+      FontConfig {
+        glyph_ranges,
+        ..Default::default()
+      },
+    );
+
+    family
+  }
+
+  /// Character drawn for glyphs missing from the family instead of the default box,
+  /// e.g. `' '` to show nothing. Ignored if the family itself doesn't have it
+  pub fn with_fallback_char(mut self, fallback: char) -> Self {
+    self.fallback_char = Some(fallback);
+    self
+  }
+
+  /// Brightens (> 1) the rasterized glyphs of every source, now and added later,
+  /// makes thin fonts more legible at small sizes (~13px), 1.0 is imgui's default
+  pub fn with_rasterizer_multiply(mut self, multiply: f32) -> Self {
+    self.rasterizer_multiply = Some(multiply);
+
+    for source in self.sources.iter_mut() {
+      if let FontSource::DefaultFontData {
+        config: Some(config),
+      }
+      | FontSource::TtfData {
+        config: Some(config),
+        ..
+      } = source
+      {
+        config.rasterizer_multiply = multiply;
+      }
     }
+
+    self
   }
 
   pub fn name(&'a self) -> &'a str {
@@ -488,73 +2352,536 @@ impl<'a> FontFamily<'a> {
     self.add_font_from_bytes_ex(data, FontConfig::default())
   }
 
-  /// Adds a TTF font to the font list with custom config
-  pub fn add_font_from_bytes_ex(&mut self, data: &'a [u8], extra_config: FontConfig) {
-    self.sources.push(FontSource::TtfData {
-      data,
-      size_pixels: self.size_pixels,
-      config: Some(FontConfig {
-        name: Some(self.name.clone()),
-        size_pixels: self.size_pixels,
-        ..extra_config
-      }),
-    });
+  /// [`Self::add_font_from_bytes`] that checks `data` starts like a TrueType/OpenType font
+  /// (or collection) first, bad data otherwise only shows up as a crash or garbage glyphs when the atlas is built.
+  ///
+  /// It's only a check of the header, a font that's cut off further in still gets through
+  pub fn try_add_font_from_bytes(&mut self, data: &'a [u8]) -> Result<(), FontError> {
+    const MAGIC: [&[u8; 4]; 5] = [b"\0\x01\0\0", b"true", b"typ1", b"OTTO", b"ttcf"];
+
+    // the table directory alone is 12 bytes
+    if data.len() < 12 || !MAGIC.iter().any(|magic| data.starts_with(*magic)) {
+      return Err(FontError::InvalidData {
+        family: self.name.clone(),
+      });
+    }
+
+    self.add_font_from_bytes(data);
+
+    Ok(())
+  }
+
+  /// Adds a TTF font to the font list with custom config.
+  ///
+  /// `name` and `size_pixels` are always replaced by the family's, and `rasterizer_multiply`
+  /// too if [`Self::with_rasterizer_multiply`] was used, every other field is kept as given
+  pub fn add_font_from_bytes_ex(&mut self, data: &'a [u8], extra_config: FontConfig) {
+    self.sources.push(FontSource::TtfData {
+      data,
+      size_pixels: self.size_pixels,
+      config: Some(FontConfig {
+        name: Some(self.name.clone()),
+        size_pixels: self.size_pixels,
+        rasterizer_multiply: self
+          .rasterizer_multiply
+          .unwrap_or(extra_config.rasterizer_multiply),
+        ..extra_config
+      }),
+    });
+  }
+
+  /// Copy of the family with every source at its size times `scale`
+  fn scaled(&self, scale: f32) -> Self {
+    let mut family = self.clone();
+    family.update_size(self.size_pixels * scale);
+    family
+  }
+
+  fn update_size(&mut self, new_size: f32) {
+    self.size_pixels = new_size;
+
+    for source in self.sources.iter_mut() {
+      match source {
+        FontSource::DefaultFontData {
+          config: Some(config),
+        } => {
+          config.size_pixels = new_size;
+        }
+        FontSource::TtfData {
+          data: _,
+          size_pixels,
+          config: Some(config),
+        } => {
+          *size_pixels = new_size;
+          config.size_pixels = new_size;
+        }
+        _ => unreachable!(),
+      }
+    }
+  }
+}
+
+/// An active touch, see [`ImGuiContext::touch_event`]
+struct Touch {
+  id: u64,
+  position: [f32; 2],
+  /// movement of the last [`TouchPhase::Moved`]
+  delta: [f32; 2],
+}
+
+/// State read at the end of a frame for queries made after it's over
+#[derive(Debug, Clone, Copy, Default)]
+struct FrameEndState {
+  any_item_hovered: bool,
+  any_item_active: bool,
+  mouse_dragging: [bool; imgui::MouseButton::COUNT],
+  any_popup_open: bool,
+  #[cfg(feature = "docking")]
+  hovered_id: u32,
+  #[cfg(feature = "docking")]
+  active_id: u32,
+}
+
+impl FrameEndState {
+  /// Must be called while the frame is still going
+  fn capture() -> Self {
+    use imgui::sys;
+
+    // the docking bindings include the internal overload taking an id
+    #[cfg(not(feature = "docking"))]
+    use sys::igIsPopupOpen as is_popup_open;
+    #[cfg(feature = "docking")]
+    use sys::igIsPopupOpen_Str as is_popup_open;
+
+    unsafe {
+      Self {
+        any_item_hovered: sys::igIsAnyItemHovered(),
+        any_item_active: sys::igIsAnyItemActive(),
+        mouse_dragging: imgui::MouseButton::VARIANTS
+          .map(|button| sys::igIsMouseDragging(button as i32, -1.)),
+        any_popup_open: is_popup_open(
+          std::ptr::null(),
+          (sys::ImGuiPopupFlags_AnyPopupId | sys::ImGuiPopupFlags_AnyPopupLevel) as i32,
+        ),
+        #[cfg(feature = "docking")]
+        hovered_id: sys::igGetHoveredID(),
+        #[cfg(feature = "docking")]
+        active_id: sys::igGetActiveID(),
+      }
+    }
+  }
+}
+
+/// Anti-aliasing flags of the style, see [`ImGuiContext::set_anti_aliasing`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AntiAliasingOptions {
+  pub lines: bool,
+  /// Uses the lines baked into the font atlas, faster and better looking for thin lines
+  pub lines_use_tex: bool,
+  pub fill: bool,
+}
+
+impl Default for AntiAliasingOptions {
+  fn default() -> Self {
+    Self {
+      lines: true,
+      lines_use_tex: true,
+      fill: true,
+    }
+  }
+}
+
+/// See [`ImGuiContext::recommend_atlas_settings`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasSettings {
+  /// for [`ImGuiContext::set_font_atlas_padding`]
+  pub glyph_padding: u32,
+  /// for [`ImGuiContext::set_font_filter`]
+  pub filter: FilterMode,
+}
+
+/// Errors of [`ImGuiContext::add_font_family`]
+///
+/// imgui (as of 1.89, what imgui-rs 0.11 binds) packs every font into a single atlas texture
+/// and its draw commands can only point at that one texture, so there's no paging to fall back on
+/// when it's too large. Lowering the font size, narrowing glyph ranges or splitting fonts between
+/// several contexts keeps it within bounds, [`ImGuiContext::font_load_report`] helps finding what's in it
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum FontAtlasError {
+  #[error("font atlas of {width}x{height} is larger than the max texture size of {max}")]
+  TooLarge { width: u32, height: u32, max: u32 },
+}
+
+/// See [`ImGuiContext::set_scissor_origin`]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ScissorOrigin {
+  /// the target's y goes down from the top like imgui's, so clip rects are flipped
+  /// for the scissor which counts from the bottom (the screen and miniquad's render targets)
+  #[default]
+  TopLeft,
+  /// the target's y already goes up from the bottom (e.g. a flipped [`ImGuiContext::set_projection`]
+  /// or a backend with top-left scissors), clip rects are used without the flip
+  BottomLeft,
+}
+
+/// Errors of [`FontFamily::try_add_font_from_bytes`]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum FontError {
+  #[error(
+    "font data added to \"{family}\" isn't a TrueType/OpenType font (wrong file or truncated?)"
+  )]
+  InvalidData { family: String },
+}
+
+/// Channel order of a bound texture's pixels, see [`ImGuiContext::bind_texture_id_with_order`]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ChannelOrder {
+  /// what miniquad and macroquad create textures with
+  #[default]
+  Rgba,
+  Bgra,
+}
+
+/// See [`ImGuiContext::font_load_report`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontLoadInfo {
+  pub handle: FontIdHandle,
+  pub name: String,
+  pub requested_glyphs: usize,
+  pub loaded_glyphs: usize,
+  /// Inclusive codepoint ranges where none of the family's sources had a glyph
+  pub missing_ranges: Vec<(u32, u32)>,
+}
+
+/// Vertex or index buffer kept across frames, see [`stream_buffer`]
+struct StreamBuffer {
+  id: BufferId,
+  /// in elements
+  capacity: usize,
+}
+
+/// Updates the `slot`th buffer of `pool` with `data` and returns it, creating the buffer if the pool
+/// isn't that large yet and growing it (delete and create again) when `data` doesn't fit. New buffers
+/// have room for at least `capacity_hint` elements
+fn stream_buffer<T>(
+  gl: &mut dyn RenderingBackend,
+  pool: &mut Vec<StreamBuffer>,
+  slot: usize,
+  buffer_type: BufferType,
+  data: &[T],
+  capacity_hint: usize,
+) -> BufferId {
+  let mut new_buffer = |capacity: usize| StreamBuffer {
+    id: gl.new_buffer(
+      buffer_type,
+      BufferUsage::Stream,
+      BufferSource::empty::<T>(capacity),
+    ),
+    capacity,
+  };
+
+  match pool.get_mut(slot) {
+    Some(buffer) if buffer.capacity >= data.len() => {}
+    Some(buffer) => {
+      let old = buffer.id;
+      // doubled so a UI that keeps growing doesn't grow the buffers every frame
+      *buffer = new_buffer((data.len() * 2).max(capacity_hint));
+      gl.delete_buffer(old);
+    }
+    None => {
+      let buffer = new_buffer(data.len().max(capacity_hint).max(1));
+      pool.push(buffer);
+    }
+  }
+
+  let id = pool[slot].id;
+  gl.buffer_update(id, BufferSource::slice(data));
+
+  id
+}
+
+/// Textures bound to imgui ids, see [`ImGuiContext::bind_texture_id`]
+struct TextureRegistry {
+  textures: HashMap<imgui::TextureId, (TextureId, ChannelOrder)>,
+  // the other way around, kept in sync with `textures`
+  imgui_ids: HashMap<TextureId, imgui::TextureId>,
+  next_id: usize,
+}
+
+impl TextureRegistry {
+  fn new() -> Self {
+    Self {
+      textures: HashMap::new(),
+      imgui_ids: HashMap::new(),
+      // 0 is reserved for the font atlas
+      next_id: 1,
+    }
+  }
+
+  /// The id `texture` is already bound to (updating its order), or a new one.
+  /// Ids aren't reused after [`Self::unbind`] so a stale id can't show another texture
+  fn bind(&mut self, texture: TextureId, order: ChannelOrder) -> imgui::TextureId {
+    if let Some(&imgui_id) = self.imgui_ids.get(&texture) {
+      self.textures.insert(imgui_id, (texture, order));
+      return imgui_id;
+    }
+
+    let imgui_id = imgui::TextureId::new(self.next_id);
+    self.next_id += 1;
+
+    self.textures.insert(imgui_id, (texture, order));
+    self.imgui_ids.insert(texture, imgui_id);
+
+    imgui_id
+  }
+
+  fn unbind(&mut self, id: imgui::TextureId) -> bool {
+    match self.textures.remove(&id) {
+      Some((texture, _)) => {
+        self.imgui_ids.remove(&texture);
+        true
+      }
+      None => false,
+    }
+  }
+
+  fn get(&self, id: imgui::TextureId) -> Option<(TextureId, ChannelOrder)> {
+    self.textures.get(&id).copied()
+  }
+}
+
+/// One draw call of a draw list, see [`draw_steps`]
+struct DrawStep {
+  texture_id: imgui::TextureId,
+  clip_rect: [f32; 4],
+  /// first vertex the indices count from
+  vtx_offset: usize,
+  /// whether `vtx_offset` changed, the vertices from it on have to go into a buffer of their own
+  new_vertex_buffer: bool,
+  index_start: usize,
+  index_count: usize,
+}
+
+/// The draw calls for `draw_list`, each index of it standing for `indices_per_index` uploaded ones
+/// (2 for the wireframe's line list). Indices are drawn in order, commands of unbound textures
+/// can be skipped without moving the others
+fn draw_steps(
+  draw_list: &imgui::DrawList,
+  indices_per_index: usize,
+) -> impl Iterator<Item = DrawStep> + '_ {
+  let mut current_vtx_offset = 0;
+  let mut index_start = 0;
+
+  draw_list.commands().filter_map(move |command| {
+    let DrawCmd::Elements { count, cmd_params } = command else {
+      return None;
+    };
+
+    let index_count = count * indices_per_index;
+    let step = DrawStep {
+      texture_id: cmd_params.texture_id,
+      clip_rect: cmd_params.clip_rect,
+      vtx_offset: cmd_params.vtx_offset,
+      new_vertex_buffer: cmd_params.vtx_offset != current_vtx_offset,
+      index_start,
+      index_count,
+    };
+
+    current_vtx_offset = cmd_params.vtx_offset;
+    index_start += index_count;
+
+    Some(step)
+  })
+}
+
+/// Line list of the edges of every triangle in `indices`, for [`ImGuiContext::set_debug_wireframe`]
+fn triangle_edges(indices: &[imgui::DrawIdx]) -> Vec<imgui::DrawIdx> {
+  indices
+    .chunks_exact(3)
+    .flat_map(|triangle| {
+      let [a, b, c] = [triangle[0], triangle[1], triangle[2]];
+      [a, b, b, c, c, a]
+    })
+    .collect()
+}
+
+/// Scissor rect (x, y from the bottom, width, height) covering `clip_rect` (min x, min y, max x, max y from the top)
+/// in a target `target_height` pixels tall.
+///
+/// The origin is floored and the extent ceiled, so a clip rect on fractional pixels
+/// covers every pixel it touches rather than losing or gaining one depending on truncation
+fn scissor_rect(clip_rect: [f32; 4], target_height: f32) -> (i32, i32, i32, i32) {
+  let [min_x, min_y, max_x, max_y] = clip_rect;
+  let (min_x, min_y) = (min_x.floor(), min_y.floor());
+  let (max_x, max_y) = (max_x.ceil(), max_y.ceil());
+
+  (
+    min_x as i32,
+    target_height.round() as i32 - max_y as i32,
+    (max_x - min_x) as i32,
+    (max_y - min_y) as i32,
+  )
+}
+
+/// Key state of `io` after `keycode` was pressed (`down`) or released with `mods` held
+fn apply_key_event(io: &mut Io, keycode: KeyCode, mods: KeyMods, down: bool) {
+  if down {
+    // when the keycode is the modifier itself - mods.MODIFIER is false yet, however the modifier button is just pressed and is actually true
+    io.key_ctrl = mods.ctrl;
+    io.key_alt = mods.alt;
+    io.key_shift = mods.shift;
+    io.key_super = mods.logo;
+  } else {
+    // when the keycode is the modifier itself - mods.MODIFIER is true, however the modifier is actually released
+    io.key_ctrl = keycode != KeyCode::LeftControl && keycode != KeyCode::RightControl && mods.ctrl;
+    io.key_alt = keycode != KeyCode::LeftAlt && keycode != KeyCode::RightAlt && mods.alt;
+    io.key_shift = keycode != KeyCode::LeftShift && keycode != KeyCode::RightShift && mods.shift;
+    io.key_super = keycode != KeyCode::LeftSuper && keycode != KeyCode::RightSuper && mods.logo;
+  }
+
+  io.keys_down[keycode as usize] = down;
+
+  // imgui only treats keypad enter like enter in some places, so it's forwarded as both
+  if keycode == KeyCode::KpEnter {
+    io.keys_down[KeyCode::Enter as usize] = down;
+  }
+}
+
+/// See [`ImGuiContext::recommend_atlas_settings`]
+fn atlas_settings_for(font_global_scale: f32, lines_use_tex: bool) -> AtlasSettings {
+  // a scale of 0 (or a negative or NaN one) would ask for u32::MAX padding,
+  // below a quarter text isn't readable anyway (`max` also turns NaN into the minimum)
+  let font_global_scale = font_global_scale.max(0.25);
+  let glyph_padding = (1. / font_global_scale).ceil().max(1.) as u32;
+
+  if (font_global_scale - 1.).abs() < f32::EPSILON && !lines_use_tex {
+    return AtlasSettings {
+      glyph_padding,
+      filter: FilterMode::Nearest,
+    };
+  }
+
+  AtlasSettings {
+    glyph_padding,
+    filter: FilterMode::Linear,
+  }
+}
+
+/// `base` with its sizes scaled by `scale`, imgui floors them while scaling
+/// so it has to start from the unscaled style every time to not drift
+fn scaled_style(mut base: imgui::Style, scale: f32) -> imgui::Style {
+  if scale != 1. {
+    base.scale_all_sizes(scale);
+  }
+
+  base
+}
+
+/// Queues `character` as text input, modifiers are left to the key events:
+/// AltGr chars report ctrl + alt on some layouts, which would otherwise stay set
+/// until the next key event and trigger shortcuts
+fn apply_char_event(io: &mut Io, character: char) {
+  // imgui-sys is built with 32 bit ImWchar, so a char outside the BMP (emoji, rare CJK) is queued whole.
+  // miniquad's Windows backend turns each UTF-16 unit of WM_CHAR into a char on its own and drops
+  // the surrogate halves, so there such chars never arrive here
+  io.add_input_character(character);
+}
+
+/// A minimized window can report a zero size, the last one is kept around instead
+/// (never below 0, imgui starts out with a negative size and asserts against it)
+fn next_display_size(last: [f32; 2], (width, height): (f32, f32)) -> [f32; 2] {
+  if width > 0. && height > 0. {
+    [width, height]
+  } else {
+    last.map(|size| size.max(0.))
+  }
+}
+
+/// imgui keeps the current context in a global, everything going through `igGetIO`
+/// (and `imgui::Context::io_mut`) lands in whichever context was made current last
+fn make_raw_current(raw_context: *mut imgui::sys::ImGuiContext) {
+  unsafe {
+    imgui::sys::igSetCurrentContext(raw_context);
   }
+}
 
-  fn update_size(&mut self, new_size: f32) {
-    self.size_pixels = new_size;
+/// Whether `data_len` bytes of RGBA8 fill the region exactly and it's a non-empty one inside the texture,
+/// raw textures come with default params so their 0x0 size never fits
+fn texture_region_fits(
+  params: &TextureParams,
+  x: i32,
+  y: i32,
+  width: i32,
+  height: i32,
+  data_len: usize,
+) -> bool {
+  let [x, y, width, height] = [x, y, width, height].map(i64::from);
+
+  params.format == TextureFormat::RGBA8
+    && x >= 0
+    && y >= 0
+    && width > 0
+    && height > 0
+    && x + width <= i64::from(params.width)
+    && y + height <= i64::from(params.height)
+    && data_len as i64 == width * height * 4
+}
 
-    for source in self.sources.iter_mut() {
-      match source {
-        FontSource::DefaultFontData {
-          config: Some(config),
-        } => {
-          config.size_pixels = new_size;
-        }
-        FontSource::TtfData {
-          data: _,
-          size_pixels,
-          config: Some(config),
-        } => {
-          *size_pixels = new_size;
-          config.size_pixels = new_size;
-        }
-        _ => unreachable!(),
-      }
-    }
-  }
+/// Id of a fixed string, it's derived from the top of the current id stack
+/// so it only comes out the same when the stack is back to the same state
+fn id_stack_probe() -> imgui::sys::ImGuiID {
+  unsafe { imgui::sys::igGetID_Str(c"##id_stack_probe".as_ptr()) }
 }
 
-/// Copied from `miniquad::graphics::TextureIdInner` because it's private I need the info
-#[allow(unused)]
-#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash)]
-enum TextureIdInnerCast {
-  Managed(usize),
-  Raw(RawId),
+/// Activates `context` whichever one was current before and returns its raw pointer,
+/// imgui-rs doesn't expose it but activating makes it the current one
+fn make_context_current(
+  context: imgui::SuspendedContext,
+) -> (imgui::Context, *mut imgui::sys::ImGuiContext) {
+  make_raw_current(std::ptr::null_mut());
+
+  let context = context
+    .activate()
+    .unwrap_or_else(|_| unreachable!("no imgui context is current"));
+
+  (context, unsafe { imgui::sys::igGetCurrentContext() })
 }
 
-fn to_imgui_id(texture_id: TextureId) -> (imgui::TextureId, TextureId) {
-  let cast = unsafe { std::mem::transmute::<TextureId, TextureIdInnerCast>(texture_id) };
+/// Queues a press (`down`) or release of `button`, events of different buttons add up
+/// rather than replacing each other's state
+fn apply_mouse_button_event(io: &mut Io, button: MouseButton, down: bool) {
+  if let Some(button) = to_imgui_mouse_button(button) {
+    io.add_mouse_button_event(button, down);
+  }
+}
 
-  match cast {
-    TextureIdInnerCast::Managed(id) => (id.into(), texture_id),
-    TextureIdInnerCast::Raw(RawId::OpenGl(id)) => ((id as usize).into(), texture_id),
-    #[cfg(target_vendor = "apple")]
-    TextureIdInnerCast::Raw(RawId::Metal(_)) => {
-      panic!("Metal not support")
-    }
+fn to_imgui_mouse_button(button: MouseButton) -> Option<imgui::MouseButton> {
+  match button {
+    MouseButton::Left => Some(imgui::MouseButton::Left),
+    MouseButton::Right => Some(imgui::MouseButton::Right),
+    MouseButton::Middle => Some(imgui::MouseButton::Middle),
+    MouseButton::Unknown => None,
   }
 }
 
-struct Clipboard;
+struct Clipboard {
+  enabled: bool,
+}
 
 impl imgui::ClipboardBackend for Clipboard {
   fn get(&mut self) -> Option<String> {
-    miniquad::window::clipboard_get()
+    if !self.enabled {
+      return None;
+    }
+
+    // imgui inserts a newline for each \r in \r\n, doubling them in multiline inputs
+    miniquad::window::clipboard_get().map(|text| text.replace('\r', ""))
   }
 
   fn set(&mut self, value: &str) {
-    miniquad::window::clipboard_set(value)
+    if self.enabled {
+      miniquad::window::clipboard_set(value)
+    }
   }
 }
 
@@ -670,13 +2997,97 @@ fn set_keymap(io: &mut Io) {
 #[cfg(feature = "macroquad")]
 mod feature_macroquad {
   use macroquad::input::utils::repeat_all_miniquad_input;
+  use macroquad::texture::{Image, Texture2D};
   use macroquad::window::get_internal_gl;
+  use miniquad::TextureParams;
 
   use super::*;
 
   impl ImGuiContext<'_> {
     pub fn update_events(&mut self) {
-      repeat_all_miniquad_input(self, self.mq_event_id);
+      self.update_events_from(self.mq_event_id);
+    }
+
+    /// Same as [`Self::update_events`] but replays the input of another subscriber,
+    /// for when input is routed through a subscriber this context doesn't own
+    pub fn update_events_from(&mut self, subscriber_id: usize) {
+      repeat_all_miniquad_input(self, subscriber_id);
+    }
+
+    /// The input subscriber registered for this context in [`ImGuiContext::new`]
+    pub fn event_subscriber_id(&self) -> usize {
+      self.mq_event_id
+    }
+
+    /// Builds and draws a frame with only `text` in a window centered on the screen and presents it,
+    /// so something shows up before a long blocking operation:
+    /// `ctx.draw_blocking_message("Loading...").await` then do the work.
+    ///
+    /// It's drawn over whatever was drawn during this frame so far, clear the screen first to show it on its own
+    pub async fn draw_blocking_message(&mut self, text: &str) {
+      self.ui(|_, ui| {
+        let [width, height] = ui.io().display_size;
+
+        ui.window("##blocking_message")
+          .position([width / 2., height / 2.], Condition::Always)
+          .position_pivot([0.5, 0.5])
+          .flags(imgui::WindowFlags::NO_DECORATION | imgui::WindowFlags::ALWAYS_AUTO_RESIZE)
+          .build(|| ui.text(text));
+      });
+
+      self.draw();
+
+      macroquad::window::next_frame().await;
+    }
+
+    /// Draws the last frame built with [`ImGuiContext::ui`] into an offscreen texture instead of the screen
+    /// and reads it back, e.g. for screenshots in docs or comparing against golden images in tests.
+    /// The background is transparent.
+    ///
+    /// Reading back stalls until the GPU is done, so it's slow, don't call it every frame.
+    ///
+    /// `None` while the display has no area to capture, e.g. while the window is minimized
+    pub fn capture_to_image(&mut self) -> Option<Image> {
+      let (width, height) = self.display_size();
+      let (width_px, height_px) = (width as u32, height as u32);
+
+      if width_px == 0 || height_px == 0 {
+        return None;
+      }
+
+      let texture = self.gl.new_render_texture(TextureParams {
+        width: width_px,
+        height: height_px,
+        ..Default::default()
+      });
+      let pass = self.gl.new_render_pass(texture, None);
+
+      self
+        .gl
+        .begin_pass(Some(pass), PassAction::clear_color(0., 0., 0., 0.));
+      self.draw_inline_sized((width, height));
+      self.gl.end_render_pass();
+
+      let row = width_px as usize * 4;
+      let mut bytes = vec![0; row * height_px as usize];
+      self.gl.texture_read_pixels(texture, &mut bytes);
+
+      // deletes the pass' color texture along with it
+      self.gl.delete_render_pass(pass);
+
+      // rows are read bottom up
+      let bytes = bytes.chunks_exact(row).rev().flatten().copied().collect();
+
+      Some(Image {
+        bytes,
+        width: width_px as u16,
+        height: height_px as u16,
+      })
+    }
+
+    /// [`ImGuiContext::bind_texture_id`] for a macroquad texture
+    pub fn bind_macroquad_texture(&mut self, texture: &Texture2D) -> imgui::TextureId {
+      self.bind_texture_id(texture.raw_miniquad_id())
     }
   }
 
@@ -689,7 +3100,807 @@ mod feature_macroquad {
   }
 }
 
+#[cfg(feature = "testing")]
+mod feature_testing {
+  use super::*;
+
+  /// A call to one of the input methods of [`EventHandler`]
+  #[derive(Debug, Clone, Copy, PartialEq)]
+  pub enum RecordedEvent {
+    MouseMotion {
+      x: f32,
+      y: f32,
+    },
+    MouseWheel {
+      x: f32,
+      y: f32,
+    },
+    MouseButtonDown {
+      button: MouseButton,
+      x: f32,
+      y: f32,
+    },
+    MouseButtonUp {
+      button: MouseButton,
+      x: f32,
+      y: f32,
+    },
+    Char {
+      character: char,
+      mods: KeyMods,
+      repeat: bool,
+    },
+    KeyDown {
+      keycode: KeyCode,
+      mods: KeyMods,
+      repeat: bool,
+    },
+    KeyUp {
+      keycode: KeyCode,
+      mods: KeyMods,
+    },
+    Touch {
+      phase: TouchPhase,
+      id: u64,
+      x: f32,
+      y: f32,
+    },
+  }
+
+  impl RecordedEvent {
+    pub fn replay(&self, handler: &mut dyn EventHandler) {
+      match *self {
+        Self::MouseMotion { x, y } => handler.mouse_motion_event(x, y),
+        Self::MouseWheel { x, y } => handler.mouse_wheel_event(x, y),
+        Self::MouseButtonDown { button, x, y } => handler.mouse_button_down_event(button, x, y),
+        Self::MouseButtonUp { button, x, y } => handler.mouse_button_up_event(button, x, y),
+        Self::Char {
+          character,
+          mods,
+          repeat,
+        } => handler.char_event(character, mods, repeat),
+        Self::KeyDown {
+          keycode,
+          mods,
+          repeat,
+        } => handler.key_down_event(keycode, mods, repeat),
+        Self::KeyUp { keycode, mods } => handler.key_up_event(keycode, mods),
+        Self::Touch { phase, id, x, y } => handler.touch_event(phase, id, x, y),
+      }
+    }
+  }
+
+  #[derive(Debug, Clone, Copy, PartialEq)]
+  pub struct TimedEvent {
+    pub frame: usize,
+    /// Seconds since the recording started
+    pub time: f64,
+    pub event: RecordedEvent,
+  }
+
+  /// Records input events, feed it like any other [`EventHandler`]
+  /// (e.g. `repeat_all_miniquad_input(&mut recorder, subscriber_id)`)
+  /// and call [`Self::next_frame`] once per frame
+  #[derive(Debug, Clone)]
+  pub struct EventRecorder {
+    start: f64,
+    frame: usize,
+    events: Vec<TimedEvent>,
+  }
+
+  impl Default for EventRecorder {
+    fn default() -> Self {
+      Self {
+        start: miniquad::date::now(),
+        frame: 0,
+        events: vec![],
+      }
+    }
+  }
+
+  impl EventRecorder {
+    pub fn next_frame(&mut self) {
+      self.frame += 1;
+    }
+
+    pub fn events(&self) -> &[TimedEvent] {
+      &self.events
+    }
+
+    pub fn into_events(self) -> Vec<TimedEvent> {
+      self.events
+    }
+
+    fn record(&mut self, event: RecordedEvent) {
+      self.events.push(TimedEvent {
+        frame: self.frame,
+        time: miniquad::date::now() - self.start,
+        event,
+      });
+    }
+  }
+
+  impl EventHandler for EventRecorder {
+    fn update(&mut self) {}
+
+    fn draw(&mut self) {}
+
+    fn mouse_motion_event(&mut self, x: f32, y: f32) {
+      self.record(RecordedEvent::MouseMotion { x, y });
+    }
+
+    fn mouse_wheel_event(&mut self, x: f32, y: f32) {
+      self.record(RecordedEvent::MouseWheel { x, y });
+    }
+
+    fn mouse_button_down_event(&mut self, button: MouseButton, x: f32, y: f32) {
+      self.record(RecordedEvent::MouseButtonDown { button, x, y });
+    }
+
+    fn mouse_button_up_event(&mut self, button: MouseButton, x: f32, y: f32) {
+      self.record(RecordedEvent::MouseButtonUp { button, x, y });
+    }
+
+    fn char_event(&mut self, character: char, mods: KeyMods, repeat: bool) {
+      self.record(RecordedEvent::Char {
+        character,
+        mods,
+        repeat,
+      });
+    }
+
+    fn key_down_event(&mut self, keycode: KeyCode, mods: KeyMods, repeat: bool) {
+      self.record(RecordedEvent::KeyDown {
+        keycode,
+        mods,
+        repeat,
+      });
+    }
+
+    fn key_up_event(&mut self, keycode: KeyCode, mods: KeyMods) {
+      self.record(RecordedEvent::KeyUp { keycode, mods });
+    }
+
+    /// Touches are recorded as they are, the mouse events miniquad emulates from them by default
+    /// come after as their own events
+    fn touch_event(&mut self, phase: TouchPhase, id: u64, x: f32, y: f32) {
+      self.record(RecordedEvent::Touch { phase, id, x, y });
+    }
+  }
+
+  /// Replays events from an [`EventRecorder`] frame by frame, deterministically
+  /// since the recorded frames are used rather than timestamps.
+  ///
+  /// With the `macroquad` feature, turn off the automatic event handling
+  /// ([`ImGuiContext::toggle_auto_trigger_event_handler`]) so real input doesn't mix in
+  #[derive(Debug, Clone)]
+  pub struct EventPlayer {
+    frame: usize,
+    next: usize,
+    events: Vec<TimedEvent>,
+  }
+
+  impl EventPlayer {
+    pub fn new(events: Vec<TimedEvent>) -> Self {
+      Self {
+        frame: 0,
+        next: 0,
+        events,
+      }
+    }
+
+    /// Feeds every event of the current frame to `handler` and moves on to the next frame
+    pub fn play_frame(&mut self, handler: &mut dyn EventHandler) {
+      while let Some(timed) = self.events.get(self.next) {
+        if timed.frame > self.frame {
+          break;
+        }
+
+        timed.event.replay(handler);
+        self.next += 1;
+      }
+
+      self.frame += 1;
+    }
+
+    pub fn is_finished(&self) -> bool {
+      self.next >= self.events.len()
+    }
+  }
+}
+
+/// Touch movement in pixels for one step of the mouse wheel when scrolling with two fingers
+const TOUCH_SCROLL_PIXELS_PER_WHEEL: f32 = 50.;
+
+/// How long [`ImGuiContext::wants_redraw`] keeps asking for frames after activity, in seconds
+const REDRAW_GRACE_PERIOD: f64 = 1.0;
+
 /// Here because borrow checker gets in the way of imgui in certain cases
 unsafe fn ignore_lifetime_mut<'a, T>(t: &mut T) -> &'a mut T {
   &mut *(t as *mut T)
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::sync::{Mutex, MutexGuard};
+
+  use miniquad::RawId;
+
+  /// imgui only allows one context at a time, tests using one take turns
+  static IMGUI: Mutex<()> = Mutex::new(());
+
+  /// imgui context without a renderer, enough to feed input through frames
+  struct Headless {
+    context: imgui::Context,
+    raw: *mut imgui::sys::ImGuiContext,
+    // dropped after the context, only the first of several contexts holds it
+    _turn: Option<MutexGuard<'static, ()>>,
+  }
+
+  impl Headless {
+    fn new() -> Self {
+      let turn = IMGUI
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+      Self::create(Some(turn))
+    }
+
+    /// Another context next to `self` (which holds the turn), it has to be dropped first
+    fn another(&self) -> Self {
+      Self::create(None)
+    }
+
+    fn create(turn: Option<MutexGuard<'static, ()>>) -> Self {
+      make_raw_current(std::ptr::null_mut());
+
+      let mut context = imgui::Context::create();
+      let raw = unsafe { imgui::sys::igGetCurrentContext() };
+      context.set_ini_filename(None);
+      set_keymap(context.io_mut());
+      context.fonts().build_rgba32_texture();
+
+      let io = context.io_mut();
+      io.display_size = [800., 600.];
+      io.delta_time = 1. / 60.;
+
+      Self {
+        context,
+        raw,
+        _turn: turn,
+      }
+    }
+
+    fn io(&mut self) -> &mut Io {
+      make_raw_current(self.raw);
+      self.context.io_mut()
+    }
+
+    fn frame<R>(&mut self, f: impl FnOnce(&Ui) -> R) -> R {
+      make_raw_current(self.raw);
+      let ui = self.context.new_frame();
+      let result = f(ui);
+      self.context.render();
+      result
+    }
+  }
+
+  impl Drop for Headless {
+    fn drop(&mut self) {
+      // imgui-rs ends the frame of the current context while dropping
+      make_raw_current(self.raw);
+    }
+  }
+
+  /// Chars the current frame received, imgui-rs' `Io::input_queue_characters` can't read an empty queue
+  fn queued_chars() -> Vec<imgui::sys::ImWchar> {
+    let queue = unsafe { &(*imgui::sys::igGetIO()).InputQueueCharacters };
+
+    if queue.Size == 0 {
+      return vec![];
+    }
+
+    unsafe { std::slice::from_raw_parts(queue.Data, queue.Size as usize) }.to_vec()
+  }
+
+  fn no_mods() -> KeyMods {
+    KeyMods {
+      shift: false,
+      ctrl: false,
+      alt: false,
+      logo: false,
+    }
+  }
+
+  fn texture(raw: u32) -> TextureId {
+    TextureId::from_raw_id(RawId::OpenGl(raw))
+  }
+
+  #[test]
+  fn alpha_discard_shares_the_fragment_shader() {
+    let fragment = shader::fragment_alpha_discard(0.25);
+
+    assert!(fragment.starts_with("#version 100\n#define ALPHA_DISCARD 0.250000\n"));
+    assert_eq!(
+      fragment.replace("#define ALPHA_DISCARD 0.250000\n", ""),
+      shader::FRAGMENT
+    );
+  }
+
+  #[test]
+  fn texture_regions_have_to_fit() {
+    let params = TextureParams {
+      width: 64,
+      height: 32,
+      ..Default::default()
+    };
+    let fits = |x, y, width, height, len| texture_region_fits(&params, x, y, width, height, len);
+
+    assert!(fits(0, 0, 64, 32, 64 * 32 * 4));
+    assert!(fits(60, 30, 4, 2, 4 * 2 * 4));
+    assert!(!fits(0, 0, 4, 4, 4 * 4 * 4 - 1));
+    assert!(!fits(0, 0, 4, 4, 4 * 4 * 3));
+    assert!(!fits(61, 0, 4, 4, 4 * 4 * 4));
+    assert!(!fits(0, 30, 4, 4, 4 * 4 * 4));
+    assert!(!fits(-1, 0, 4, 4, 4 * 4 * 4));
+    assert!(!fits(0, 0, -4, -4, 16 * 4));
+    assert!(!fits(0, 0, 0, 4, 0));
+    assert!(!fits(i32::MAX, 0, 1, 1, 4));
+
+    let alpha = TextureParams {
+      format: TextureFormat::Alpha,
+      ..params
+    };
+    assert!(!texture_region_fits(&alpha, 0, 0, 4, 4, 4 * 4 * 4));
+
+    // what miniquad reports for a texture bound from a raw GL id
+    assert!(!texture_region_fits(
+      &TextureParams::default(),
+      0,
+      0,
+      1,
+      1,
+      4
+    ));
+    assert!(!texture_region_fits(
+      &TextureParams::default(),
+      0,
+      0,
+      0,
+      0,
+      0
+    ));
+  }
+
+  #[test]
+  fn texture_ids_start_after_the_font_atlas() {
+    let mut registry = TextureRegistry::new();
+
+    assert_eq!(registry.bind(texture(7), ChannelOrder::Rgba).id(), 1);
+    assert_eq!(registry.bind(texture(3), ChannelOrder::Rgba).id(), 2);
+  }
+
+  #[test]
+  fn binding_again_returns_the_same_id() {
+    let mut registry = TextureRegistry::new();
+
+    let id = registry.bind(texture(1), ChannelOrder::Rgba);
+    let other = registry.bind(texture(2), ChannelOrder::Rgba);
+
+    assert_eq!(registry.bind(texture(1), ChannelOrder::Bgra), id);
+    assert_eq!(registry.get(id), Some((texture(1), ChannelOrder::Bgra)));
+    assert_eq!(registry.get(other), Some((texture(2), ChannelOrder::Rgba)));
+
+    assert!(registry.unbind(other));
+    assert_eq!(registry.bind(texture(1), ChannelOrder::Rgba), id);
+  }
+
+  #[test]
+  fn unbound_ids_are_not_reused() {
+    let mut registry = TextureRegistry::new();
+
+    let id = registry.bind(texture(1), ChannelOrder::Rgba);
+
+    assert!(registry.unbind(id));
+    assert!(!registry.unbind(id));
+    assert_eq!(registry.get(id), None);
+
+    let rebound = registry.bind(texture(1), ChannelOrder::Rgba);
+
+    assert_ne!(rebound, id);
+    assert_eq!(
+      registry.get(rebound),
+      Some((texture(1), ChannelOrder::Rgba))
+    );
+    assert_eq!(registry.bind(texture(1), ChannelOrder::Rgba), rebound);
+  }
+
+  #[test]
+  fn keypad_enter_submits_input_text() {
+    let mut imgui = Headless::new();
+    let mut text = String::from("hello");
+
+    let mut input = |imgui: &mut Headless, focus: bool| {
+      imgui.frame(|ui| {
+        ui.window("input").build(|| {
+          if focus {
+            ui.set_keyboard_focus_here();
+          }
+
+          ui.input_text("##input", &mut text)
+            .enter_returns_true(true)
+            .build()
+        })
+      })
+    };
+
+    input(&mut imgui, true);
+    assert_eq!(input(&mut imgui, false), Some(false));
+
+    apply_key_event(imgui.io(), KeyCode::KpEnter, no_mods(), true);
+    assert_eq!(input(&mut imgui, false), Some(true));
+
+    apply_key_event(imgui.io(), KeyCode::KpEnter, no_mods(), false);
+    assert!(!imgui.io().keys_down[KeyCode::Enter as usize]);
+  }
+
+  #[test]
+  fn adopted_contexts_become_current() {
+    let _turn = IMGUI
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    let first = imgui::SuspendedContext::create();
+    let second = imgui::Context::create();
+    let second_raw = unsafe { imgui::sys::igGetCurrentContext() };
+
+    let (first, first_raw) = make_context_current(first);
+    assert_ne!(first_raw, second_raw);
+    assert_eq!(unsafe { imgui::sys::igGetCurrentContext() }, first_raw);
+
+    make_raw_current(second_raw);
+    drop(second);
+    make_raw_current(first_raw);
+    drop(first);
+  }
+
+  #[test]
+  fn input_goes_to_the_context_made_current() {
+    let mut first = Headless::new();
+    let mut second = first.another();
+
+    // the second context is current after being created, the first one's handlers make it current again
+    make_raw_current(first.raw);
+    apply_key_event(first.context.io_mut(), KeyCode::A, no_mods(), true);
+    make_raw_current(first.raw);
+    apply_char_event(first.context.io_mut(), 'a');
+
+    assert_eq!(second.frame(|_| queued_chars()), []);
+    assert!(!second.io().keys_down[KeyCode::A as usize]);
+    assert_eq!(
+      first.frame(|_| queued_chars()),
+      ['a' as imgui::sys::ImWchar]
+    );
+    assert!(first.io().keys_down[KeyCode::A as usize]);
+  }
+
+  #[test]
+  fn id_stack_probe_follows_pushes() {
+    let mut imgui = Headless::new();
+
+    imgui.frame(|ui| {
+      let probe = id_stack_probe();
+
+      let token = ui.push_id("pushed");
+      assert_ne!(id_stack_probe(), probe);
+      token.pop();
+      assert_eq!(id_stack_probe(), probe);
+
+      ui.window("window")
+        .build(|| ui.push_id("in a window").pop());
+      assert_eq!(id_stack_probe(), probe);
+    });
+  }
+
+  #[test]
+  fn chars_outside_the_bmp_are_queued_whole() {
+    let mut imgui = Headless::new();
+
+    apply_char_event(imgui.io(), '\u{1F600}');
+
+    assert_eq!(imgui.frame(|_| queued_chars()), [0x1F600]);
+  }
+
+  #[test]
+  fn alt_gr_chars_leave_no_modifiers_behind() {
+    let mut imgui = Headless::new();
+    let mut text = String::new();
+    let ctrl_alt = KeyMods {
+      ctrl: true,
+      alt: true,
+      ..no_mods()
+    };
+
+    let mut input = |imgui: &mut Headless, focus: bool| {
+      imgui.frame(|ui| {
+        ui.window("input").build(|| {
+          if focus {
+            ui.set_keyboard_focus_here();
+          }
+
+          ui.input_text("##input", &mut text).build();
+        });
+      })
+    };
+
+    input(&mut imgui, true);
+    input(&mut imgui, false);
+
+    // AltGr + q on a German layout under Windows: a fake left ctrl comes along with right alt
+    let io = imgui.io();
+    apply_key_event(
+      io,
+      KeyCode::LeftControl,
+      KeyMods {
+        ctrl: true,
+        ..no_mods()
+      },
+      true,
+    );
+    apply_key_event(io, KeyCode::RightAlt, ctrl_alt, true);
+    apply_key_event(io, KeyCode::Q, ctrl_alt, true);
+    apply_char_event(io, '@');
+    apply_key_event(io, KeyCode::Q, ctrl_alt, false);
+    apply_key_event(io, KeyCode::RightAlt, ctrl_alt, false);
+    apply_key_event(
+      io,
+      KeyCode::LeftControl,
+      KeyMods {
+        ctrl: true,
+        ..no_mods()
+      },
+      false,
+    );
+    input(&mut imgui, false);
+
+    let io = imgui.io();
+    assert!(!io.key_ctrl && !io.key_alt);
+    assert_eq!(text, "@");
+  }
+
+  #[test]
+  fn clipped_tables_past_16_bit_indices_draw_from_their_vertex_offsets() {
+    let mut imgui = Headless::new();
+    let io = imgui.io();
+    io.backend_flags
+      .insert(imgui::BackendFlags::RENDERER_HAS_VTX_OFFSET);
+    // tall enough for the rows left after clipping to need more than 65536 vertices
+    io.display_size = [4096., 16384.];
+
+    let ui = imgui.context.new_frame();
+    let mut rows_drawn = 0;
+    ui.window("table")
+      .position([0., 0.], imgui::Condition::Always)
+      .size([4096., 16384.], imgui::Condition::Always)
+      .build(|| {
+        // imgui-rs' table api is behind a feature this crate doesn't enable
+        let flags =
+          (imgui::sys::ImGuiTableFlags_ScrollY | imgui::sys::ImGuiTableFlags_RowBg) as i32;
+        let size = imgui::sys::ImVec2 { x: 0., y: 0. };
+        if !unsafe { imgui::sys::igBeginTable(c"rows".as_ptr(), 8, flags, size, 0.) } {
+          return;
+        }
+
+        let mut clipper = imgui::ListClipper::new(100_000).begin(ui);
+        while clipper.step() {
+          for row in clipper.display_start()..clipper.display_end() {
+            unsafe { imgui::sys::igTableNextRow(0, 0.) };
+            for column in 0..8 {
+              unsafe { imgui::sys::igTableNextColumn() };
+              ui.text(format!("{row}:{column}"));
+            }
+            rows_drawn += 1;
+          }
+        }
+
+        unsafe { imgui::sys::igEndTable() };
+      });
+    let draw_data = imgui.context.render();
+
+    assert!(
+      rows_drawn > 0 && rows_drawn < 2_000,
+      "{rows_drawn} rows drawn"
+    );
+
+    let mut biggest = 0;
+    let mut offsets = 0;
+    for draw_list in draw_data.draw_lists() {
+      let (vertices, indices) = (draw_list.vtx_buffer(), draw_list.idx_buffer());
+      biggest = biggest.max(vertices.len());
+
+      let mut last_offset = 0;
+      let mut drawn = 0;
+      for step in draw_steps(draw_list, 1) {
+        assert_eq!(step.new_vertex_buffer, step.vtx_offset != last_offset);
+        assert_eq!(step.index_start, drawn);
+        last_offset = step.vtx_offset;
+        offsets += step.new_vertex_buffer as usize;
+        drawn += step.index_count;
+
+        // the indices index into the vertex buffer streamed from the offset on
+        let offset_vertices = vertices.len() - step.vtx_offset;
+        for &index in &indices[step.index_start..step.index_start + step.index_count] {
+          assert!((index as usize) < offset_vertices);
+        }
+      }
+      assert_eq!(drawn, indices.len());
+
+      let edges: usize = draw_steps(draw_list, 2).map(|step| step.index_count).sum();
+      assert_eq!(edges, triangle_edges(indices).len());
+    }
+
+    assert!(biggest > 65_536, "biggest draw list has {biggest} vertices");
+    assert!(offsets > 0);
+  }
+
+  #[cfg(feature = "testing")]
+  #[test]
+  fn touches_are_recorded_and_replayed() {
+    let mut recorder = EventRecorder::default();
+    recorder.touch_event(TouchPhase::Started, 3, 10., 20.);
+    recorder.next_frame();
+    recorder.touch_event(TouchPhase::Ended, 3, 12., 24.);
+
+    let events = recorder.into_events();
+    let mut player = EventPlayer::new(events.clone());
+    let mut replayed = EventRecorder::default();
+
+    player.play_frame(&mut replayed);
+    player.play_frame(&mut replayed);
+
+    assert!(player.is_finished());
+
+    let touches =
+      |events: &[TimedEvent]| events.iter().map(|timed| timed.event).collect::<Vec<_>>();
+
+    assert_eq!(touches(replayed.events()), touches(&events));
+    assert_eq!(
+      events[1].event,
+      RecordedEvent::Touch {
+        phase: TouchPhase::Ended,
+        id: 3,
+        x: 12.,
+        y: 24.,
+      }
+    );
+  }
+
+  #[test]
+  fn mouse_buttons_map_to_imgui() {
+    assert_eq!(
+      to_imgui_mouse_button(MouseButton::Left),
+      Some(imgui::MouseButton::Left)
+    );
+    assert_eq!(
+      to_imgui_mouse_button(MouseButton::Right),
+      Some(imgui::MouseButton::Right)
+    );
+    assert_eq!(
+      to_imgui_mouse_button(MouseButton::Middle),
+      Some(imgui::MouseButton::Middle)
+    );
+    assert_eq!(to_imgui_mouse_button(MouseButton::Unknown), None);
+  }
+
+  #[test]
+  fn mouse_buttons_are_held_at_the_same_time() {
+    let mut imgui = Headless::new();
+    let down = |imgui: &mut Headless| {
+      imgui.frame(|_| ());
+      let io = imgui.io();
+      [io.mouse_down[0], io.mouse_down[1], io.mouse_down[2]]
+    };
+
+    apply_mouse_button_event(imgui.io(), MouseButton::Left, true);
+    apply_mouse_button_event(imgui.io(), MouseButton::Right, true);
+    apply_mouse_button_event(imgui.io(), MouseButton::Unknown, true);
+    assert_eq!(down(&mut imgui), [true, true, false]);
+
+    apply_mouse_button_event(imgui.io(), MouseButton::Left, false);
+    assert_eq!(down(&mut imgui), [false, true, false]);
+
+    apply_mouse_button_event(imgui.io(), MouseButton::Right, false);
+    assert_eq!(down(&mut imgui), [false, false, false]);
+  }
+
+  #[test]
+  fn zero_display_size_keeps_the_last_one() {
+    assert_eq!(
+      next_display_size([800., 600.], (1024., 768.)),
+      [1024., 768.]
+    );
+    assert_eq!(next_display_size([800., 600.], (0., 0.)), [800., 600.]);
+    assert_eq!(next_display_size([800., 600.], (1024., 0.)), [800., 600.]);
+    assert_eq!(next_display_size([-1., -1.], (0., 0.)), [0., 0.]);
+  }
+
+  #[test]
+  fn scissor_rect_covers_fractional_pixels() {
+    assert_eq!(
+      scissor_rect([0.5, 0.5, 100.5, 100.5], 600.),
+      (0, 499, 101, 101)
+    );
+    assert_eq!(scissor_rect([0., 0., 800., 600.], 600.), (0, 0, 800, 600));
+    assert_eq!(scissor_rect([10., 20., 30., 40.], 599.6), (10, 560, 20, 20));
+  }
+
+  #[test]
+  fn triangle_edges_outline_every_triangle() {
+    assert_eq!(
+      triangle_edges(&[0, 1, 2, 2, 3, 0]),
+      [0, 1, 1, 2, 2, 0, 2, 3, 3, 0, 0, 2]
+    );
+    assert_eq!(triangle_edges(&[0, 1]), []);
+  }
+
+  #[test]
+  fn ui_scale_starts_from_the_unscaled_style() {
+    let imgui = Headless::new();
+    let base = *imgui.context.style();
+    let sizes = |style: imgui::Style| {
+      (
+        style.window_padding,
+        style.item_spacing,
+        style.scrollbar_size,
+      )
+    };
+
+    // 8 * 1.3 floored, scaling that back by 1 / 1.3 would floor it to 7 rather than the 8 it started at
+    assert_eq!(sizes(scaled_style(base, 1.)), sizes(base));
+    assert_eq!(scaled_style(base, 1.3).window_padding, [10., 10.]);
+    assert_eq!(
+      scaled_style(base, 2.).item_spacing,
+      base.item_spacing.map(|size| size * 2.)
+    );
+  }
+
+  #[test]
+  fn degenerate_font_scales_get_bounded_padding() {
+    for scale in [0., -1., f32::NAN, f32::NEG_INFINITY, 0.01] {
+      assert_eq!(atlas_settings_for(scale, false).glyph_padding, 4);
+    }
+
+    assert_eq!(atlas_settings_for(f32::INFINITY, false).glyph_padding, 1);
+  }
+
+  #[test]
+  fn baked_lines_keep_the_atlas_linear() {
+    let settings = |glyph_padding, filter| AtlasSettings {
+      glyph_padding,
+      filter,
+    };
+
+    assert_eq!(
+      atlas_settings_for(1., false),
+      settings(1, FilterMode::Nearest)
+    );
+    assert_eq!(
+      atlas_settings_for(1., true),
+      settings(1, FilterMode::Linear)
+    );
+    assert_eq!(
+      atlas_settings_for(0.5, false),
+      settings(2, FilterMode::Linear)
+    );
+    assert_eq!(
+      atlas_settings_for(0.3, true),
+      settings(4, FilterMode::Linear)
+    );
+    assert_eq!(
+      atlas_settings_for(2., false),
+      settings(1, FilterMode::Linear)
+    );
+  }
+}